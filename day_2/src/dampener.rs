@@ -0,0 +1,251 @@
+use crate::rules::{Rules, Trend};
+
+fn fits(last_value: u32, value: u32, rules: &Rules, trend: Trend) -> bool {
+    let follows_trend = match trend {
+        Trend::Increasing => last_value <= value,
+        Trend::Decreasing => last_value >= value,
+        Trend::Either => true,
+    };
+
+    follows_trend && rules.step_allowed(last_value.abs_diff(value))
+}
+
+// A report is safe with Either if it is safe read as increasing or as decreasing
+fn trends(rules: &Rules) -> &'static [Trend] {
+    match rules.direction {
+        Trend::Increasing => &[Trend::Increasing],
+        Trend::Decreasing => &[Trend::Decreasing],
+        Trend::Either => &[Trend::Increasing, Trend::Decreasing],
+    }
+}
+
+// Longest subsequence of the report that is safe when read in the given trend.
+// longest[i] is the longest safe subsequence ending with level i.
+fn longest_safe(line: &[u32], rules: &Rules, trend: Trend) -> usize {
+    let mut longest: Vec<usize> = vec![1; line.len()];
+
+    for i in 1..line.len() {
+        for j in 0..i {
+            if fits(line[j], line[i], rules, trend) {
+                longest[i] = longest[i].max(longest[j] + 1);
+            }
+        }
+    }
+
+    longest.into_iter().max().unwrap_or(0)
+}
+
+// Minimum number of levels that must be removed to make the report safe, or
+// None if no subsequence long enough satisfies the rules
+pub fn min_removals(line: &[u32], rules: &Rules) -> Option<usize> {
+    let longest = trends(rules)
+        .iter()
+        .map(|&trend| longest_safe(line, rules, trend))
+        .max()
+        .unwrap_or(0);
+
+    if longest < rules.min_length.max(1) {
+        return None;
+    }
+
+    Some(line.len() - longest)
+}
+
+// First adjacent pair of kept levels (by index) that breaks the rules, reading
+// the report in a fixed trend and ignoring the level at `skip`
+fn first_violation(
+    line: &[u32],
+    rules: &Rules,
+    trend: Trend,
+    skip: Option<usize>,
+) -> Option<(usize, usize)> {
+    let mut previous: Option<usize> = None;
+
+    for i in 0..line.len() {
+        if skip == Some(i) {
+            continue;
+        }
+
+        if let Some(p) = previous {
+            if !fits(line[p], line[i], rules, trend) {
+                return Some((p, i));
+            }
+        }
+
+        previous = Some(i);
+    }
+
+    None
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Dampened {
+    Safe,
+    RemoveLevel(usize),
+    Unsafe,
+}
+
+// Part 2 in linear time. With a fixed trend only adjacent levels constrain each
+// other, so if the first violation is between levels p and i, any single removal
+// that makes the report safe has to remove one of those two.
+pub fn dampen(line: &[u32], rules: &Rules) -> Dampened {
+    let min_length = rules.min_length.max(1);
+
+    let mut violations: Vec<(Trend, (usize, usize))> = Vec::new();
+
+    for &trend in trends(rules) {
+        match first_violation(line, rules, trend, None) {
+            None if line.len() >= min_length => return Dampened::Safe,
+            None => return Dampened::Unsafe,
+            Some(pair) => violations.push((trend, pair)),
+        }
+    }
+
+    if line.len() <= min_length {
+        return Dampened::Unsafe;
+    }
+
+    for (trend, (previous, current)) in violations {
+        for skip in [previous, current] {
+            if first_violation(line, rules, trend, Some(skip)).is_none() {
+                return Dampened::RemoveLevel(skip);
+            }
+        }
+    }
+
+    Dampened::Unsafe
+}
+
+pub fn safe_with_one_removal(line: &[u32], rules: &Rules) -> bool {
+    dampen(line, rules) != Dampened::Unsafe
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::check_line;
+
+    // A small xorshift generator so the checks are repeatable without a
+    // dependency
+    struct Random(u64);
+
+    impl Random {
+        fn below(&mut self, bound: u32) -> u32 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            (self.0 % bound as u64) as u32
+        }
+    }
+
+    fn brute_force(line: &[u32], rules: &Rules) -> bool {
+        check_line(line, rules)
+            || (0..line.len()).any(|i| {
+                let mut removed = line.to_vec();
+                removed.remove(i);
+                check_line(&removed, rules)
+            })
+    }
+
+    fn random_rules(random: &mut Random) -> Rules {
+        let min_step = random.below(3);
+
+        Rules {
+            min_step,
+            max_step: min_step + random.below(4),
+            allow_plateaus: random.below(2) == 0,
+            direction: [Trend::Increasing, Trend::Decreasing, Trend::Either]
+                [random.below(3) as usize],
+            min_length: random.below(5) as usize,
+        }
+    }
+
+    // Short reports of small values, so steps of every size and plenty of
+    // repeats and direction changes come up
+    fn random_line(random: &mut Random) -> Vec<u32> {
+        let length = random.below(9);
+        let start = random.below(10);
+
+        (0..length)
+            .scan(start, |value, _| {
+                *value = (*value + random.below(9)).saturating_sub(4);
+                Some(*value)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn dampen_matches_brute_force() {
+        let mut random = Random(0x2024_0002);
+
+        for _ in 0..20_000 {
+            let rules = random_rules(&mut random);
+            let line = random_line(&mut random);
+
+            assert_eq!(
+                safe_with_one_removal(&line, &rules),
+                brute_force(&line, &rules),
+                "{:?} with {:?}",
+                line,
+                rules
+            );
+        }
+    }
+
+    #[test]
+    fn removed_level_makes_report_safe() {
+        let mut random = Random(0x2024_0202);
+
+        for _ in 0..20_000 {
+            let rules = random_rules(&mut random);
+            let line = random_line(&mut random);
+
+            match dampen(&line, &rules) {
+                Dampened::Safe => assert!(check_line(&line, &rules)),
+                Dampened::RemoveLevel(i) => {
+                    let mut removed = line.clone();
+                    removed.remove(i);
+                    assert!(check_line(&removed, &rules), "{:?} without {}", line, i);
+                }
+                Dampened::Unsafe => {}
+            }
+        }
+    }
+
+    #[test]
+    fn min_removals_agrees_with_dampen() {
+        let mut random = Random(0x2024_2002);
+
+        for _ in 0..20_000 {
+            let rules = random_rules(&mut random);
+            let line = random_line(&mut random);
+
+            assert_eq!(
+                min_removals(&line, &rules).is_some_and(|k| k <= 1),
+                safe_with_one_removal(&line, &rules),
+                "{:?} with {:?}",
+                line,
+                rules
+            );
+        }
+    }
+
+    #[test]
+    fn puzzle_example() {
+        let rules = Rules::default();
+        let reports: [&[u32]; 6] = [
+            &[7, 6, 4, 2, 1],
+            &[1, 2, 7, 8, 9],
+            &[9, 7, 6, 2, 1],
+            &[1, 3, 2, 4, 5],
+            &[8, 6, 4, 4, 1],
+            &[1, 3, 6, 7, 9],
+        ];
+        let safe: Vec<bool> = reports
+            .iter()
+            .map(|line| safe_with_one_removal(line, &rules))
+            .collect();
+
+        assert_eq!(safe, [true, false, false, true, true, true]);
+    }
+}
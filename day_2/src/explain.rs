@@ -0,0 +1,65 @@
+use crate::dampener::{dampen, Dampened};
+use crate::rules::{find_violation, Reason, Rules};
+use serde::Serialize;
+
+#[derive(Debug, Serialize)]
+pub struct Diagnosis {
+    pub report: usize,
+    pub levels: Vec<u32>,
+    pub safe: bool,
+    pub offending_index: Option<usize>,
+    pub reason: Option<Reason>,
+    pub dampened_safe: bool,
+    pub removed_index: Option<usize>,
+}
+
+pub fn diagnose(report: usize, line: &[u32], rules: &Rules) -> Diagnosis {
+    let violation = find_violation(line, rules);
+    let dampened = dampen(line, rules);
+
+    Diagnosis {
+        report,
+        levels: line.to_vec(),
+        safe: violation.is_none(),
+        offending_index: violation.and_then(|v| v.index),
+        reason: violation.map(|v| v.reason),
+        dampened_safe: dampened != Dampened::Unsafe,
+        removed_index: match dampened {
+            Dampened::RemoveLevel(index) => Some(index),
+            _ => None,
+        },
+    }
+}
+
+fn format_optional<T: ToString>(value: Option<T>) -> String {
+    value.map_or("-".to_string(), |v| v.to_string())
+}
+
+pub fn print_table(diagnoses: &[Diagnosis]) {
+    println!(
+        "{:>6}  {:<6}  {:>5}  {:<16}  {:<8}  {:>7}  levels",
+        "report", "safe", "index", "reason", "dampened", "removed"
+    );
+
+    for d in diagnoses {
+        let levels: Vec<String> = d.levels.iter().map(|l| l.to_string()).collect();
+
+        println!(
+            "{:>6}  {:<6}  {:>5}  {:<16}  {:<8}  {:>7}  {}",
+            d.report,
+            if d.safe { "yes" } else { "no" },
+            format_optional(d.offending_index),
+            format_optional(d.reason.map(|r| r.describe())),
+            if d.dampened_safe { "yes" } else { "no" },
+            format_optional(d.removed_index),
+            levels.join(" ")
+        );
+    }
+}
+
+pub fn print_json(diagnoses: &[Diagnosis]) {
+    println!(
+        "{}",
+        serde_json::to_string_pretty(diagnoses).expect("Could not serialise diagnoses")
+    );
+}
@@ -1,88 +1,189 @@
+mod dampener;
+mod explain;
+mod rules;
+mod stats;
+
+use dampener::{min_removals, safe_with_one_removal};
+use explain::{diagnose, print_json, print_table, Diagnosis};
+use rules::{check_line, Rules, Trend};
 use std::env;
 use std::fs;
 
-fn check_line(line: &Vec<u32>) -> bool {
-    if line.len() < 2 {
-        return false;
+fn usage(program: &str) -> ! {
+    eprintln!(
+        "Usage: {} --input <filename> [--rules <file.toml>] [--min-step <n>] [--max-step <n>] \
+         [--allow-plateaus] [--direction <increasing|decreasing|either>] [--min-length <n>] \
+         [--tolerance <k>] [--removals] [--explain <table|json>] \
+         [--stats <table|json>]",
+        program
+    );
+    std::process::exit(1);
+}
+
+fn parse_value<T: std::str::FromStr>(program: &str, flag: &str, value: Option<&String>) -> T {
+    match value.map(|v| v.parse::<T>()) {
+        Some(Ok(parsed)) => parsed,
+        _ => {
+            eprintln!("Invalid or missing value for {}", flag);
+            usage(program);
+        }
     }
+}
 
-    let mut increasing = true;
-    let mut last_value = line[0];
+fn main() {
+    let args: Vec<String> = env::args().collect();
 
-    if last_value > line[1] {
-        increasing = false;
+    let mut filename: Option<String> = None;
+    let mut rules_file: Option<String> = None;
+    let mut overrides: Vec<(String, Option<String>)> = Vec::new();
+    let mut tolerance: Option<usize> = None;
+    let mut show_removals = false;
+    let mut explain: Option<String> = None;
+    let mut stats: Option<String> = None;
+
+    // --input <filename> plus optional rule flags
+    let mut i = 1;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--input" => {
+                filename = Some(parse_value(&args[0], "--input", args.get(i + 1)));
+                i += 1;
+            }
+            "--rules" => {
+                rules_file = Some(parse_value(&args[0], "--rules", args.get(i + 1)));
+                i += 1;
+            }
+            "--tolerance" => {
+                tolerance = Some(parse_value(&args[0], "--tolerance", args.get(i + 1)));
+                i += 1;
+            }
+            "--removals" => show_removals = true,
+            "--explain" => {
+                explain = Some(parse_value(&args[0], "--explain", args.get(i + 1)));
+                i += 1;
+            }
+            "--stats" => {
+                stats = Some(parse_value(&args[0], "--stats", args.get(i + 1)));
+                i += 1;
+            }
+            "--allow-plateaus" => overrides.push((args[i].clone(), None)),
+            "--min-step" | "--max-step" | "--direction" | "--min-length" => {
+                overrides.push((args[i].clone(), args.get(i + 1).cloned()));
+                i += 1;
+            }
+            _ => usage(&args[0]),
+        }
+        i += 1;
     }
 
-    for i in 1..line.len() {
-        // Do the faster check first
-        if increasing && last_value > line[i] {
-            return false;
-        } else if !increasing && last_value < line[i] {
-            return false;
+    let Some(filename) = filename else {
+        usage(&args[0]);
+    };
+
+    // Flags given on the command line take precedence over the rules file
+    let mut rules = match rules_file {
+        Some(rules_file) => Rules::from_file(&rules_file).unwrap_or_else(|e| {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }),
+        None => Rules::default(),
+    };
+
+    for (flag, value) in &overrides {
+        match flag.as_str() {
+            "--allow-plateaus" => rules.allow_plateaus = true,
+            "--min-step" => rules.min_step = parse_value(&args[0], flag, value.as_ref()),
+            "--max-step" => rules.max_step = parse_value(&args[0], flag, value.as_ref()),
+            "--min-length" => rules.min_length = parse_value(&args[0], flag, value.as_ref()),
+            "--direction" => {
+                let value: String = parse_value(&args[0], flag, value.as_ref());
+                rules.direction = Trend::parse(&value).unwrap_or_else(|e| {
+                    eprintln!("{}", e);
+                    std::process::exit(1);
+                });
+            }
+            _ => unreachable!(),
         }
+    }
 
-        let diff = last_value.abs_diff(line[i]);
+    if let Err(e) = rules.validate() {
+        eprintln!("Invalid rules: {}", e);
+        std::process::exit(1);
+    }
 
-        if diff == 0 || diff > 3 {
-            return false;
+    let contents = fs::read_to_string(&filename).expect("Something went wrong reading the file");
+
+    let lines = contents
+        .lines()
+        .map(|x| x.trim())
+        .filter(|x| !x.is_empty())
+        .map(|x| {
+            x.split_whitespace()
+                .map(|y| y.parse::<u32>().expect("Not a number"))
+                .collect::<Vec<u32>>()
+        });
+
+    // The explanation replaces the normal output so JSON stays machine readable
+    if let Some(format) = explain {
+        let diagnoses: Vec<Diagnosis> = lines
+            .clone()
+            .enumerate()
+            .map(|(i, line)| diagnose(i + 1, &line, &rules))
+            .collect();
+
+        match format.as_str() {
+            "table" => print_table(&diagnoses),
+            "json" => print_json(&diagnoses),
+            _ => usage(&args[0]),
         }
 
-        last_value = line[i];
+        return;
     }
 
-    true
-}
+    if let Some(format) = stats {
+        let reports: Vec<Vec<u32>> = lines.clone().collect();
+        let statistics = stats::collect(&reports, &rules);
 
-fn main() {
-    let args: Vec<String> = env::args().collect();
+        match format.as_str() {
+            "table" => stats::print_table(&statistics),
+            "json" => stats::print_json(&statistics),
+            _ => usage(&args[0]),
+        }
 
-    // --input <filename>
-    if args.len() != 3 {
-        eprintln!("Usage: {} --input <filename>", args[0]);
-        std::process::exit(1);
+        return;
     }
 
-    if args[1] != "--input" {
-        eprintln!("Usage: {} --input <filename>", args[0]);
-        std::process::exit(1);
-    }
-
-    let filename = &args[2];
-    let contents = fs::read_to_string(filename).expect("Something went wrong reading the file");
-
-    let lines = contents.split("\r\n").map(|x| x.trim()).map(|x| {
-        x.split(" ")
-            .map(|y| y.parse::<u32>().expect("Not a number"))
-            .collect()
-    });
-
     // PART 1
 
-    let part1_result = lines.clone().filter(|x| check_line(x)).count();
+    let part1_result = lines.clone().filter(|x| check_line(x, &rules)).count();
     println!("Part 1: {}", part1_result);
 
     // PART 2
 
     let part2_result = lines
         .clone()
-        .filter(|x| {
-            let checked = check_line(x);
-            if !checked {
-                for i in 0..x.len() {
-                    let mut new_line = x.clone();
-                    new_line.remove(i);
-
-                    if check_line(&new_line) {
-                        return true;
-                    }
-                }
-            } else {
-                return true;
-            }
-
-            false
-        })
+        .filter(|x| safe_with_one_removal(x, &rules))
         .count();
 
     println!("Part 2: {}", part2_result);
+
+    // PART 2 generalised to k removed levels
+
+    if let Some(tolerance) = tolerance {
+        let result = lines
+            .clone()
+            .filter(|x| min_removals(x, &rules).is_some_and(|removals| removals <= tolerance))
+            .count();
+
+        println!("Part 2 (up to {} removals): {}", tolerance, result);
+    }
+
+    if show_removals {
+        for (i, line) in lines.clone().enumerate() {
+            match min_removals(&line, &rules) {
+                Some(removals) => println!("Report {}: {} removals", i + 1, removals),
+                None => println!("Report {}: cannot be made safe", i + 1),
+            }
+        }
+    }
 }
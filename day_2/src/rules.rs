@@ -0,0 +1,153 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Trend {
+    Increasing,
+    Decreasing,
+    Either,
+}
+
+impl Trend {
+    pub fn parse(value: &str) -> Result<Trend, String> {
+        match value {
+            "increasing" => Ok(Trend::Increasing),
+            "decreasing" => Ok(Trend::Decreasing),
+            "either" => Ok(Trend::Either),
+            _ => Err(format!(
+                "Invalid direction '{}', expected increasing, decreasing or either",
+                value
+            )),
+        }
+    }
+}
+
+// The defaults are the puzzle's rules: strictly monotonic with steps of 1..=3
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Rules {
+    pub min_step: u32,
+    pub max_step: u32,
+    pub allow_plateaus: bool,
+    pub direction: Trend,
+    pub min_length: usize,
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Rules {
+            min_step: 1,
+            max_step: 3,
+            allow_plateaus: false,
+            direction: Trend::Either,
+            min_length: 2,
+        }
+    }
+}
+
+impl Rules {
+    pub fn from_file(filename: &str) -> Result<Rules, String> {
+        let contents = fs::read_to_string(filename)
+            .map_err(|e| format!("Could not read rules file {}: {}", filename, e))?;
+
+        toml::from_str(&contents).map_err(|e| format!("Invalid rules file {}: {}", filename, e))
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.min_step > self.max_step {
+            return Err(format!(
+                "min_step ({}) is greater than max_step ({})",
+                self.min_step, self.max_step
+            ));
+        }
+
+        Ok(())
+    }
+
+    pub fn step_allowed(&self, diff: u32) -> bool {
+        if diff == 0 {
+            return self.allow_plateaus;
+        }
+
+        diff >= self.min_step && diff <= self.max_step
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Reason {
+    TooShort,
+    DirectionChange,
+    ZeroStep,
+    StepTooSmall,
+    StepTooLarge,
+}
+
+impl Reason {
+    pub fn describe(&self) -> &'static str {
+        match self {
+            Reason::TooShort => "too short",
+            Reason::DirectionChange => "direction change",
+            Reason::ZeroStep => "zero step",
+            Reason::StepTooSmall => "step too small",
+            Reason::StepTooLarge => "step too large",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Violation {
+    // Index of the first level that breaks the rules, None if the report is too short
+    pub index: Option<usize>,
+    pub reason: Reason,
+}
+
+pub fn find_violation(line: &[u32], rules: &Rules) -> Option<Violation> {
+    if line.len() < rules.min_length.max(1) {
+        return Some(Violation {
+            index: None,
+            reason: Reason::TooShort,
+        });
+    }
+
+    // With Either, the first non-flat step decides the direction for the rest
+    let mut trend = rules.direction;
+
+    for (i, pair) in line.windows(2).enumerate() {
+        let (last_value, value) = (pair[0], pair[1]);
+        let violation = |reason| {
+            Some(Violation {
+                index: Some(i + 1),
+                reason,
+            })
+        };
+
+        // Do the faster check first
+        match trend {
+            Trend::Increasing if last_value > value => return violation(Reason::DirectionChange),
+            Trend::Decreasing if last_value < value => return violation(Reason::DirectionChange),
+            Trend::Either if last_value < value => trend = Trend::Increasing,
+            Trend::Either if last_value > value => trend = Trend::Decreasing,
+            _ => {}
+        }
+
+        let diff = last_value.abs_diff(value);
+
+        if !rules.step_allowed(diff) {
+            return violation(if diff == 0 {
+                Reason::ZeroStep
+            } else if diff < rules.min_step {
+                Reason::StepTooSmall
+            } else {
+                Reason::StepTooLarge
+            });
+        }
+    }
+
+    None
+}
+
+pub fn check_line(line: &[u32], rules: &Rules) -> bool {
+    find_violation(line, rules).is_none()
+}
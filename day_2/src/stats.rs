@@ -0,0 +1,147 @@
+use crate::dampener::min_removals;
+use crate::rules::{find_violation, Reason, Rules};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+const BAR_WIDTH: usize = 40;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportDirection {
+    Increasing,
+    Decreasing,
+    Flat,
+    Mixed,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReasonCount {
+    pub reason: Reason,
+    pub count: usize,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct Statistics {
+    pub reports: usize,
+    pub safe: usize,
+    pub step_sizes: BTreeMap<u32, usize>,
+    pub directions: BTreeMap<ReportDirection, usize>,
+    pub lengths: BTreeMap<usize, usize>,
+    // Most common first
+    pub failure_reasons: Vec<ReasonCount>,
+    pub removals_needed: BTreeMap<usize, usize>,
+    pub unfixable: usize,
+}
+
+fn report_direction(line: &[u32]) -> ReportDirection {
+    let rising = line.windows(2).any(|pair| pair[0] < pair[1]);
+    let falling = line.windows(2).any(|pair| pair[0] > pair[1]);
+
+    match (rising, falling) {
+        (true, false) => ReportDirection::Increasing,
+        (false, true) => ReportDirection::Decreasing,
+        (false, false) => ReportDirection::Flat,
+        (true, true) => ReportDirection::Mixed,
+    }
+}
+
+pub fn collect(lines: &[Vec<u32>], rules: &Rules) -> Statistics {
+    let mut stats = Statistics::default();
+    let mut reasons: BTreeMap<Reason, usize> = BTreeMap::new();
+
+    for line in lines {
+        stats.reports += 1;
+
+        for pair in line.windows(2) {
+            *stats
+                .step_sizes
+                .entry(pair[0].abs_diff(pair[1]))
+                .or_insert(0) += 1;
+        }
+
+        *stats.directions.entry(report_direction(line)).or_insert(0) += 1;
+        *stats.lengths.entry(line.len()).or_insert(0) += 1;
+
+        match find_violation(line, rules) {
+            Some(violation) => *reasons.entry(violation.reason).or_insert(0) += 1,
+            None => stats.safe += 1,
+        }
+
+        match min_removals(line, rules) {
+            Some(removals) => *stats.removals_needed.entry(removals).or_insert(0) += 1,
+            None => stats.unfixable += 1,
+        }
+    }
+
+    stats.failure_reasons = reasons
+        .into_iter()
+        .map(|(reason, count)| ReasonCount { reason, count })
+        .collect();
+    stats
+        .failure_reasons
+        .sort_by(|a, b| b.count.cmp(&a.count).then(a.reason.cmp(&b.reason)));
+
+    stats
+}
+
+fn print_histogram(title: &str, rows: &[(String, usize)]) {
+    let max = rows.iter().map(|(_, count)| *count).max().unwrap_or(0);
+
+    println!("{}", title);
+
+    for (label, count) in rows {
+        let bar = (count * BAR_WIDTH).checked_div(max).unwrap_or(0);
+        println!("  {:>16}  {:>6}  {}", label, count, "#".repeat(bar));
+    }
+
+    println!();
+}
+
+pub fn print_table(stats: &Statistics) {
+    println!("Reports: {}", stats.reports);
+    println!("Safe: {}", stats.safe);
+    println!();
+
+    let step_sizes: Vec<(String, usize)> = stats
+        .step_sizes
+        .iter()
+        .map(|(step, count)| (step.to_string(), *count))
+        .collect();
+    print_histogram("Step sizes", &step_sizes);
+
+    let directions: Vec<(String, usize)> = stats
+        .directions
+        .iter()
+        .map(|(direction, count)| (format!("{:?}", direction).to_lowercase(), *count))
+        .collect();
+    print_histogram("Directions", &directions);
+
+    let lengths: Vec<(String, usize)> = stats
+        .lengths
+        .iter()
+        .map(|(length, count)| (length.to_string(), *count))
+        .collect();
+    print_histogram("Report lengths", &lengths);
+
+    let reasons: Vec<(String, usize)> = stats
+        .failure_reasons
+        .iter()
+        .map(|r| (r.reason.describe().to_string(), r.count))
+        .collect();
+    print_histogram("Failure reasons", &reasons);
+
+    let mut removals: Vec<(String, usize)> = stats
+        .removals_needed
+        .iter()
+        .map(|(removals, count)| (removals.to_string(), *count))
+        .collect();
+    removals.push(("unfixable".to_string(), stats.unfixable));
+    print_histogram("Removals needed", &removals);
+}
+
+pub fn print_json(stats: &Statistics) {
+    println!(
+        "{}",
+        serde_json::to_string_pretty(stats).expect("Could not serialise statistics")
+    );
+}
@@ -0,0 +1,238 @@
+use std::fmt;
+
+// What an instruction does when executed. New instructions are declared by
+// mapping their syntax onto one of these in the registry.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Effect {
+    Add(i64),
+    Enable,
+    Disable,
+    Reset,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct InstructionSpec {
+    // Recognised as `name(arg,...)` with min_args..=max_args numeric arguments
+    pub name: &'static str,
+    pub min_args: usize,
+    pub max_args: usize,
+    // None if the result doesn't fit in 64 bits
+    pub semantics: fn(&[u64]) -> Option<Effect>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Instruction {
+    // Index of the spec in the registry that recognised the instruction
+    pub spec: usize,
+    pub args: Vec<u64>,
+    // Where the instruction's raw text sits in the input, in bytes
+    pub offset: usize,
+    pub length: usize,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Overflow {
+    pub name: &'static str,
+    pub args: Vec<u64>,
+    pub offset: usize,
+}
+
+impl fmt::Display for Overflow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let args: Vec<String> = self.args.iter().map(|a| a.to_string()).collect();
+        write!(
+            f,
+            "Overflow evaluating {}({}) at byte offset {}",
+            self.name,
+            args.join(","),
+            self.offset
+        )
+    }
+}
+
+fn product(args: &[u64]) -> Option<i64> {
+    args.iter()
+        .try_fold(1i64, |acc, &a| acc.checked_mul(i64::try_from(a).ok()?))
+}
+
+fn sum(args: &[u64]) -> Option<i64> {
+    args.iter()
+        .try_fold(0i64, |acc, &a| acc.checked_add(i64::try_from(a).ok()?))
+}
+
+fn difference(args: &[u64]) -> Option<i64> {
+    i64::try_from(args[0])
+        .ok()?
+        .checked_sub(i64::try_from(args[1]).ok()?)
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Registry {
+    specs: Vec<InstructionSpec>,
+}
+
+impl Registry {
+    pub fn new() -> Self {
+        Registry { specs: Vec::new() }
+    }
+
+    // The instructions from the puzzle
+    pub fn standard() -> Self {
+        let mut registry = Registry::new();
+
+        for spec in [
+            InstructionSpec {
+                name: "mul",
+                min_args: 2,
+                max_args: 2,
+                semantics: |args| product(args).map(Effect::Add),
+            },
+            InstructionSpec {
+                name: "do",
+                min_args: 0,
+                max_args: 0,
+                semantics: |_| Some(Effect::Enable),
+            },
+            InstructionSpec {
+                name: "don't",
+                min_args: 0,
+                max_args: 0,
+                semantics: |_| Some(Effect::Disable),
+            },
+        ] {
+            registry.register(spec).unwrap();
+        }
+
+        registry
+    }
+
+    // The puzzle's instructions plus add, sub, reset and a variadic mul
+    pub fn extended() -> Self {
+        let mut registry = Registry::new();
+
+        for spec in [
+            InstructionSpec {
+                name: "mul",
+                min_args: 2,
+                max_args: 8,
+                semantics: |args| product(args).map(Effect::Add),
+            },
+            InstructionSpec {
+                name: "add",
+                min_args: 2,
+                max_args: 8,
+                semantics: |args| sum(args).map(Effect::Add),
+            },
+            InstructionSpec {
+                name: "sub",
+                min_args: 2,
+                max_args: 2,
+                semantics: |args| difference(args).map(Effect::Add),
+            },
+            InstructionSpec {
+                name: "reset",
+                min_args: 0,
+                max_args: 0,
+                semantics: |_| Some(Effect::Reset),
+            },
+            InstructionSpec {
+                name: "do",
+                min_args: 0,
+                max_args: 0,
+                semantics: |_| Some(Effect::Enable),
+            },
+            InstructionSpec {
+                name: "don't",
+                min_args: 0,
+                max_args: 0,
+                semantics: |_| Some(Effect::Disable),
+            },
+        ] {
+            registry.register(spec).unwrap();
+        }
+
+        registry
+    }
+
+    pub fn register(&mut self, spec: InstructionSpec) -> Result<(), String> {
+        if spec.name.is_empty() || spec.name.contains(['(', ')', ',']) {
+            return Err(format!("Invalid instruction name '{}'", spec.name));
+        }
+
+        if spec.min_args > spec.max_args {
+            return Err(format!(
+                "Instruction '{}' has min_args greater than max_args",
+                spec.name
+            ));
+        }
+
+        if self.find(spec.name.as_bytes()).is_some() {
+            return Err(format!("Instruction '{}' is already registered", spec.name));
+        }
+
+        self.specs.push(spec);
+        Ok(())
+    }
+
+    pub fn get(&self, index: usize) -> &InstructionSpec {
+        &self.specs[index]
+    }
+
+    pub fn find(&self, name: &[u8]) -> Option<usize> {
+        self.specs.iter().position(|s| s.name.as_bytes() == name)
+    }
+
+    // Whether prefix followed by byte is still the start of some name
+    pub fn continues_name(&self, prefix: &[u8], byte: u8) -> bool {
+        self.specs.iter().any(|s| {
+            let name = s.name.as_bytes();
+            name.len() > prefix.len() && name.starts_with(prefix) && name[prefix.len()] == byte
+        })
+    }
+}
+
+// Runs recognised instructions. Part 1 ignores do() and don't(), so it runs on
+// an interpreter that isn't conditional.
+pub struct Interpreter {
+    pub sum: i64,
+    pub enabled: bool,
+    conditional: bool,
+}
+
+impl Interpreter {
+    pub fn new(conditional: bool) -> Self {
+        Interpreter {
+            sum: 0,
+            enabled: true,
+            conditional,
+        }
+    }
+
+    pub fn execute(
+        &mut self,
+        registry: &Registry,
+        instruction: &Instruction,
+    ) -> Result<(), Overflow> {
+        let spec = registry.get(instruction.spec);
+        let overflow = || Overflow {
+            name: spec.name,
+            args: instruction.args.clone(),
+            offset: instruction.offset,
+        };
+
+        // Only do() and don't() still run while disabled
+        let active = self.enabled || !self.conditional;
+
+        match (spec.semantics)(&instruction.args).ok_or_else(overflow)? {
+            Effect::Add(value) if active => {
+                self.sum = self.sum.checked_add(value).ok_or_else(overflow)?;
+            }
+            Effect::Reset if active => self.sum = 0,
+            Effect::Enable => self.enabled = true,
+            Effect::Disable => self.enabled = false,
+            _ => {}
+        }
+
+        Ok(())
+    }
+}
@@ -1,65 +1,310 @@
+mod instructions;
+mod program;
+mod scanner;
+mod trace;
+
+use instructions::{Instruction, Interpreter, Registry};
 use regex::Regex;
+use scanner::{Scanner, DEFAULT_DIGITS, MAX_DIGITS};
 use std::env;
-use std::fs;
+use std::fs::{self, File};
+use std::io::{self, Read};
 
-const PART_1: &str = r"mul\((\d{1,3}),(\d{1,3})\)";
-const PART_2: &str = r"do\(\)|don't\(\)|mul\((\d{1,3}),(\d{1,3})\)";
+// Operands are limited to a configurable number of digits, 3 in the puzzle
+fn part_1_regex(digits: usize) -> String {
+    format!(r"mul\((\d{{1,{0}}}),(\d{{1,{0}}})\)", digits)
+}
 
-fn main() {
-    let args: Vec<String> = env::args().collect();
+fn part_2_regex(digits: usize) -> String {
+    format!(r"do\(\)|don't\(\)|{}", part_1_regex(digits))
+}
 
-    // --input <filename>
-    if args.len() != 3 {
-        eprintln!("Usage: {} --input <filename>", args[0]);
-        std::process::exit(1);
-    }
+fn usage(program: &str) -> ! {
+    eprintln!(
+        "Usage: {} (--input <filename|-> | --program <filename>) [--regex | --extended] \
+         [--trace | --highlight | --disassemble] [--digits <1-{}>]",
+        program, MAX_DIGITS
+    );
+    std::process::exit(1);
+}
 
-    if args[1] != "--input" {
-        eprintln!("Usage: {} --input <filename>", args[0]);
-        std::process::exit(1);
+// Executes instructions as they arrive, computing both parts in one pass
+fn run(
+    instructions: impl Iterator<Item = Result<Instruction, String>>,
+    registry: &Registry,
+) -> Result<(i64, i64), String> {
+    let mut part_1 = Interpreter::new(false);
+    let mut part_2 = Interpreter::new(true);
+
+    for instruction in instructions {
+        let instruction = instruction?;
+        part_1
+            .execute(registry, &instruction)
+            .map_err(|e| e.to_string())?;
+        part_2
+            .execute(registry, &instruction)
+            .map_err(|e| e.to_string())?;
     }
 
-    let filename = &args[2];
-    let contents = fs::read_to_string(filename).expect("Something went wrong reading the file");
+    Ok((part_1.sum, part_2.sum))
+}
+
+// Scans the input in constant memory
+fn scan(reader: impl Read, registry: &Registry, digits: usize) -> Result<(i64, i64), String> {
+    let instructions = Scanner::new(reader, registry, digits)
+        .map(|i| i.map_err(|e| format!("Something went wrong reading: {}", e)));
+
+    run(instructions, registry)
+}
+
+fn checked_product(a: &str, b: &str) -> Result<i64, String> {
+    let a: i64 = a
+        .parse()
+        .map_err(|_| format!("Operand {} doesn't fit in 64 bits", a))?;
+    let b: i64 = b
+        .parse()
+        .map_err(|_| format!("Operand {} doesn't fit in 64 bits", b))?;
+    a.checked_mul(b)
+        .ok_or_else(|| format!("Overflow evaluating mul({},{})", a, b))
+}
+
+fn checked_add(sum: i64, value: i64) -> Result<i64, String> {
+    sum.checked_add(value)
+        .ok_or_else(|| "Overflow accumulating the sum".to_string())
+}
 
+// The original regex implementation, kept to cross-check the scanner
+fn regex_sums(contents: &str, digits: usize) -> Result<(i64, i64), String> {
     // Part 1
 
-    let part_1: u32 = Regex::new(PART_1)
-        .unwrap()
-        .captures_iter(&contents)
-        .map(|capture| {
-            let a: u32 = capture[1].parse().unwrap();
-            let b: u32 = capture[2].parse().unwrap();
-            a * b
-        })
-        .sum();
+    let mut part_1: i64 = 0;
 
-    println!("Part 1: {}", part_1);
+    for capture in Regex::new(&part_1_regex(digits))
+        .unwrap()
+        .captures_iter(contents)
+    {
+        part_1 = checked_add(part_1, checked_product(&capture[1], &capture[2])?)?;
+    }
 
     // Part 2
 
     let mut multiply = true;
+    let mut part_2: i64 = 0;
 
-    let part_2: u32 = Regex::new(PART_2)
+    for capture in Regex::new(&part_2_regex(digits))
         .unwrap()
-        .captures_iter(&contents)
-        .map(|capture| {
-            if capture.get(0).unwrap().as_str() == "do()" {
-                multiply = true;
-                return 0;
-            } else if capture.get(0).unwrap().as_str() == "don't()" {
-                multiply = false;
-                return 0;
-            } else {
-                let a: u32 = capture[1].parse().unwrap();
-                let b: u32 = capture[2].parse().unwrap();
-                if multiply {
-                    return a * b;
-                }
+        .captures_iter(contents)
+    {
+        if capture.get(0).unwrap().as_str() == "do()" {
+            multiply = true;
+        } else if capture.get(0).unwrap().as_str() == "don't()" {
+            multiply = false;
+        } else if multiply {
+            part_2 = checked_add(part_2, checked_product(&capture[1], &capture[2])?)?;
+        }
+    }
+
+    Ok((part_1, part_2))
+}
+
+fn read_all(filename: &str) -> Vec<u8> {
+    if filename == "-" {
+        let mut contents = Vec::new();
+        io::stdin()
+            .read_to_end(&mut contents)
+            .expect("Something went wrong reading stdin");
+        contents
+    } else {
+        fs::read(filename).expect("Something went wrong reading the file")
+    }
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+
+    let mut filename: Option<String> = None;
+    let mut program_file: Option<String> = None;
+    let mut disassemble = false;
+    let mut use_regex = false;
+    let mut registry = Registry::standard();
+    let mut trace_mode: Option<String> = None;
+    let mut digits = DEFAULT_DIGITS;
+
+    // (--input <filename|-> | --program <filename>) plus options
+    let mut i = 1;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--input" => {
+                filename = args.get(i + 1).cloned();
+                i += 1;
+            }
+            "--program" => {
+                program_file = args.get(i + 1).cloned();
+                i += 1;
             }
-            return 0;
-        })
-        .sum();
+            "--disassemble" => disassemble = true,
+            "--regex" => use_regex = true,
+            "--extended" => registry = Registry::extended(),
+            "--trace" | "--highlight" => trace_mode = Some(args[i].clone()),
+            "--digits" => {
+                digits = match args.get(i + 1).map(|d| d.parse::<usize>()) {
+                    Some(Ok(d)) if (1..=MAX_DIGITS).contains(&d) => d,
+                    _ => usage(&args[0]),
+                };
+                i += 1;
+            }
+            _ => usage(&args[0]),
+        }
+        i += 1;
+    }
+
+    // A clean program is executed directly, without scanning for corruption
+    if let Some(program_file) = program_file {
+        let contents = read_all(&program_file);
+        let (part_1, part_2) = program::load(&contents, &registry, digits)
+            .and_then(|program| run(program.into_iter().map(Ok), &registry))
+            .unwrap_or_else(|e| {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            });
+
+        println!("Part 1: {}", part_1);
+        println!("Part 2: {}", part_2);
+        return;
+    }
+
+    let Some(filename) = filename else {
+        usage(&args[0]);
+    };
+
+    if disassemble {
+        let stdout = io::stdout().lock();
+        let result = if filename == "-" {
+            program::disassemble(io::stdin().lock(), &registry, digits, stdout)
+        } else {
+            let file = File::open(&filename).expect("Something went wrong reading the file");
+            program::disassemble(file, &registry, digits, stdout)
+        };
+
+        result.expect("Something went wrong writing the program");
+        return;
+    }
 
+    // Tracing refers back to the original text, so it reads the whole input
+    if let Some(mode) = trace_mode {
+        let contents = read_all(&filename);
+        let events = trace::trace(&contents, &registry, digits).unwrap_or_else(|e| {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        });
+
+        if mode == "--trace" {
+            trace::print_trace(&contents, &events);
+        } else {
+            trace::print_highlighted(&contents, &events);
+        }
+
+        return;
+    }
+
+    let result = if use_regex {
+        let contents = read_all(&filename);
+        regex_sums(&String::from_utf8_lossy(&contents), digits)
+    } else if filename == "-" {
+        scan(io::stdin().lock(), &registry, digits)
+    } else {
+        let file = File::open(&filename).expect("Something went wrong reading the file");
+        scan(file, &registry, digits)
+    };
+
+    let (part_1, part_2) = result.unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    });
+
+    println!("Part 1: {}", part_1);
     println!("Part 2: {}", part_2);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scan_str(contents: &str, digits: usize) -> Result<(i64, i64), String> {
+        scan(contents.as_bytes(), &Registry::standard(), digits)
+    }
+
+    #[test]
+    fn scanner_matches_regex_on_input() {
+        let contents =
+            fs::read_to_string(concat!(env!("CARGO_MANIFEST_DIR"), "/input.txt")).unwrap();
+
+        assert_eq!(
+            scan_str(&contents, DEFAULT_DIGITS),
+            regex_sums(&contents, DEFAULT_DIGITS)
+        );
+    }
+
+    #[test]
+    fn scanner_matches_regex_on_fragments() {
+        let fragments = [
+            "xmul(2,4)%&mul[3,7]!@^do_not_mul(5,5)+mul(32,64]then(mul(11,8)mul(8,5))",
+            "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))",
+            "mul(1234,5)",
+            "mul(123,4567)mul(12,3)",
+            "mmul(2,3)",
+            "mulmul(2,3)",
+            "mul(mul(2,3)",
+            "mul(2,mul(4,5)",
+            "don't(do()mul(2,3)",
+            "don't()don't(do()mul(2,3)",
+            "do(don't()mul(2,3)",
+            "dodon't()mul(2,3)do()mul(4,5)",
+            "mul(1,2",
+            "mul(7,8)mul(1,2",
+            "mul(1,",
+            "mul(",
+            "don't(",
+            "mul(,2)mul(1,)mul()mul(1,2,3)",
+            "mul( 1,2)mul(1 ,2)mul(-1,2)",
+            "",
+        ];
+
+        for fragment in fragments {
+            for digits in 1..=4 {
+                assert_eq!(
+                    scan_str(fragment, digits),
+                    regex_sums(fragment, digits),
+                    "{:?} with {} digits",
+                    fragment,
+                    digits
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn widest_operands_fit() {
+        let largest = "9".repeat(MAX_DIGITS);
+        let contents = format!("mul({0},1)don't()mul(1,{0})", largest);
+        let expected: i64 = largest.parse().unwrap();
+
+        assert_eq!(
+            scan_str(&contents, MAX_DIGITS),
+            Ok((2 * expected, expected))
+        );
+        assert_eq!(
+            regex_sums(&contents, MAX_DIGITS),
+            Ok((2 * expected, expected))
+        );
+    }
+
+    #[test]
+    fn puzzle_examples() {
+        let part_1 = "xmul(2,4)%&mul[3,7]!@^do_not_mul(5,5)+mul(32,64]then(mul(11,8)mul(8,5))";
+        let part_2 = "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))";
+
+        assert_eq!(scan_str(part_1, DEFAULT_DIGITS).unwrap().0, 161);
+        assert_eq!(scan_str(part_2, DEFAULT_DIGITS).unwrap().1, 48);
+    }
+}
@@ -0,0 +1,71 @@
+use crate::instructions::{Instruction, Registry};
+use crate::scanner::Scanner;
+use std::io::{self, Read, Write};
+
+pub fn format_instruction(registry: &Registry, instruction: &Instruction) -> String {
+    let args: Vec<String> = instruction.args.iter().map(|a| a.to_string()).collect();
+    format!(
+        "{}({})",
+        registry.get(instruction.spec).name,
+        args.join(",")
+    )
+}
+
+// Writes the instructions recovered from corrupted memory as a clean program,
+// one instruction per line
+pub fn disassemble(
+    reader: impl Read,
+    registry: &Registry,
+    digits: usize,
+    mut writer: impl Write,
+) -> io::Result<()> {
+    for instruction in Scanner::new(reader, registry, digits) {
+        writeln!(writer, "{}", format_instruction(registry, &instruction?))?;
+    }
+
+    writer.flush()
+}
+
+// Parses a clean program. Every non-blank line has to be exactly one
+// instruction; anything else is rejected rather than skipped as corruption.
+pub fn load(
+    contents: &[u8],
+    registry: &Registry,
+    digits: usize,
+) -> Result<Vec<Instruction>, String> {
+    let mut program: Vec<Instruction> = Vec::new();
+    let mut line_start = 0;
+
+    for (i, line) in contents.split_inclusive(|&b| b == b'\n').enumerate() {
+        let offset = line_start;
+        line_start += line.len();
+
+        let text = line.trim_ascii();
+        if text.is_empty() {
+            continue;
+        }
+
+        let invalid = || {
+            format!(
+                "Line {}: '{}' is not a single instruction",
+                i + 1,
+                String::from_utf8_lossy(text)
+            )
+        };
+
+        let mut instruction = match Scanner::new(text, registry, digits).next() {
+            Some(Ok(instruction))
+                if instruction.offset == 0 && instruction.length == text.len() =>
+            {
+                instruction
+            }
+            _ => return Err(invalid()),
+        };
+
+        // Report positions in the program file rather than within the line
+        instruction.offset = offset + line.len() - line.trim_ascii_start().len();
+        program.push(instruction);
+    }
+
+    Ok(program)
+}
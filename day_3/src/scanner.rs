@@ -0,0 +1,179 @@
+use crate::instructions::{Instruction, Registry};
+use std::collections::VecDeque;
+use std::io::{self, BufReader, Bytes, Read};
+
+// The puzzle's operands are 1-3 digits
+pub const DEFAULT_DIGITS: usize = 3;
+// Instructions evaluate to i64, and any operand of up to 18 digits fits in one
+pub const MAX_DIGITS: usize = 18;
+
+#[derive(Clone, Debug)]
+enum State {
+    // The pending bytes are a prefix of at least one instruction name
+    Name,
+    // name(a,b... where digits counts the digits of the last argument so far
+    Args {
+        spec: usize,
+        args: Vec<u64>,
+        digits: usize,
+    },
+}
+
+enum Step {
+    Next(State),
+    Emit { spec: usize, args: Vec<u64> },
+    Fail,
+}
+
+fn step(registry: &Registry, max_digits: usize, pending: &[u8], state: State, byte: u8) -> Step {
+    match state {
+        State::Name => {
+            if byte == b'(' {
+                return match registry.find(pending) {
+                    Some(spec) => Step::Next(State::Args {
+                        spec,
+                        args: Vec::new(),
+                        digits: 0,
+                    }),
+                    None => Step::Fail,
+                };
+            }
+
+            if registry.continues_name(pending, byte) {
+                Step::Next(State::Name)
+            } else {
+                Step::Fail
+            }
+        }
+        State::Args {
+            spec,
+            mut args,
+            digits,
+        } => {
+            let instruction = registry.get(spec);
+
+            match byte {
+                b'0'..=b'9' if digits == 0 && args.len() < instruction.max_args => {
+                    args.push((byte - b'0') as u64);
+                    Step::Next(State::Args {
+                        spec,
+                        args,
+                        digits: 1,
+                    })
+                }
+                b'0'..=b'9' if digits > 0 && digits < max_digits => {
+                    let last = args.last_mut().unwrap();
+                    *last = *last * 10 + (byte - b'0') as u64;
+                    Step::Next(State::Args {
+                        spec,
+                        args,
+                        digits: digits + 1,
+                    })
+                }
+                b',' if digits > 0 => Step::Next(State::Args {
+                    spec,
+                    args,
+                    digits: 0,
+                }),
+                // No trailing comma, so either no arguments or a finished last one
+                b')' if (digits > 0 || args.is_empty()) && args.len() >= instruction.min_args => {
+                    Step::Emit { spec, args }
+                }
+                _ => Step::Fail,
+            }
+        }
+    }
+}
+
+// Recognises the instructions in a registry one byte at a time, so only the
+// reader's buffer and the current partial match are held in memory regardless
+// of the size of the input.
+//
+// When a partial match fails, everything after its first byte is replayed so
+// an instruction starting inside it is still found, giving the same leftmost
+// matches as a regex over the whole input. A partial match is never longer
+// than the longest name plus its arguments, so the replay stays small.
+pub struct Scanner<'a, R: Read> {
+    bytes: Bytes<BufReader<R>>,
+    registry: &'a Registry,
+    max_digits: usize,
+    state: State,
+    pending: Vec<u8>,
+    replay: VecDeque<u8>,
+    // Offset of the next byte to be read and of the first pending byte
+    offset: usize,
+    start: usize,
+}
+
+impl<'a, R: Read> Scanner<'a, R> {
+    // Operands are limited to max_digits digits, which must be 1..=MAX_DIGITS
+    pub fn new(reader: R, registry: &'a Registry, max_digits: usize) -> Self {
+        assert!((1..=MAX_DIGITS).contains(&max_digits));
+
+        Scanner {
+            bytes: BufReader::new(reader).bytes(),
+            registry,
+            max_digits,
+            state: State::Name,
+            pending: Vec::new(),
+            replay: VecDeque::new(),
+            offset: 0,
+            start: 0,
+        }
+    }
+
+    fn next_byte(&mut self) -> Option<io::Result<u8>> {
+        if self.pending.is_empty() {
+            self.start = self.offset;
+        }
+        self.offset += 1;
+
+        match self.replay.pop_front() {
+            Some(byte) => Some(Ok(byte)),
+            None => self.bytes.next(),
+        }
+    }
+}
+
+impl<R: Read> Iterator for Scanner<'_, R> {
+    type Item = io::Result<Instruction>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(byte) = self.next_byte() {
+            let byte = match byte {
+                Ok(byte) => byte,
+                Err(e) => return Some(Err(e)),
+            };
+
+            let state = std::mem::replace(&mut self.state, State::Name);
+
+            match step(self.registry, self.max_digits, &self.pending, state, byte) {
+                Step::Next(state) => {
+                    self.state = state;
+                    self.pending.push(byte);
+                }
+                Step::Emit { spec, args } => {
+                    self.pending.clear();
+                    return Some(Ok(Instruction {
+                        spec,
+                        args,
+                        offset: self.start,
+                        length: self.offset - self.start,
+                    }));
+                }
+                Step::Fail => {
+                    self.pending.push(byte);
+
+                    for &byte in self.pending[1..].iter().rev() {
+                        self.replay.push_front(byte);
+                    }
+
+                    self.pending.clear();
+                    self.offset = self.start + 1;
+                }
+            }
+        }
+
+        None
+    }
+}
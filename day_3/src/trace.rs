@@ -0,0 +1,175 @@
+use crate::instructions::{Effect, Interpreter, Overflow, Registry};
+use crate::scanner::Scanner;
+
+const RESET: &str = "\x1b[0m";
+const DIM: &str = "\x1b[2m";
+const GREEN: &str = "\x1b[32m";
+const RED: &str = "\x1b[31m";
+const CYAN: &str = "\x1b[36m";
+
+#[derive(Debug)]
+pub enum EventKind {
+    Corrupted,
+    Instruction {
+        effect: Effect,
+        // The interpreter state when the instruction was reached
+        enabled: bool,
+        // Change to the part 2 sum
+        contribution: i128,
+    },
+}
+
+#[derive(Debug)]
+pub struct Event {
+    pub offset: usize,
+    pub length: usize,
+    pub line: usize,
+    pub column: usize,
+    pub kind: EventKind,
+}
+
+// Tracks the 1-based line and column of a byte offset, moving forwards only
+struct Cursor<'a> {
+    contents: &'a [u8],
+    offset: usize,
+    line: usize,
+    column: usize,
+}
+
+impl Cursor<'_> {
+    fn advance_to(&mut self, offset: usize) -> (usize, usize) {
+        for &byte in &self.contents[self.offset..offset] {
+            if byte == b'\n' {
+                self.line += 1;
+                self.column = 1;
+            } else {
+                self.column += 1;
+            }
+        }
+
+        self.offset = offset;
+        (self.line, self.column)
+    }
+}
+
+fn push_corrupted(events: &mut Vec<Event>, cursor: &mut Cursor, from: usize, to: usize) {
+    if from < to {
+        let (line, column) = cursor.advance_to(from);
+        events.push(Event {
+            offset: from,
+            length: to - from,
+            line,
+            column,
+            kind: EventKind::Corrupted,
+        });
+    }
+}
+
+// Every instruction in the input along with the corrupted regions between them
+pub fn trace(
+    contents: &[u8],
+    registry: &Registry,
+    max_digits: usize,
+) -> Result<Vec<Event>, Overflow> {
+    let mut events: Vec<Event> = Vec::new();
+    let mut interpreter = Interpreter::new(true);
+    let mut cursor = Cursor {
+        contents,
+        offset: 0,
+        line: 1,
+        column: 1,
+    };
+    let mut end = 0;
+
+    for instruction in Scanner::new(contents, registry, max_digits) {
+        let instruction = instruction.expect("Reading from memory can't fail");
+
+        push_corrupted(&mut events, &mut cursor, end, instruction.offset);
+
+        let enabled = interpreter.enabled;
+        let before = interpreter.sum;
+        interpreter.execute(registry, &instruction)?;
+
+        // execute already checked that the semantics don't overflow
+        let effect = (registry.get(instruction.spec).semantics)(&instruction.args).unwrap();
+
+        let (line, column) = cursor.advance_to(instruction.offset);
+        events.push(Event {
+            offset: instruction.offset,
+            length: instruction.length,
+            line,
+            column,
+            kind: EventKind::Instruction {
+                effect,
+                enabled,
+                contribution: interpreter.sum as i128 - before as i128,
+            },
+        });
+
+        end = instruction.offset + instruction.length;
+    }
+
+    push_corrupted(&mut events, &mut cursor, end, contents.len());
+
+    Ok(events)
+}
+
+fn text<'a>(contents: &'a [u8], event: &Event) -> std::borrow::Cow<'a, str> {
+    String::from_utf8_lossy(&contents[event.offset..event.offset + event.length])
+}
+
+pub fn print_trace(contents: &[u8], events: &[Event]) {
+    println!(
+        "{:>8}  {:>10}  {:<10}  {:<7}  {:>12}  text",
+        "offset", "line:col", "kind", "enabled", "contribution"
+    );
+
+    for event in events {
+        let position = format!("{}:{}", event.line, event.column);
+        let text = text(contents, event);
+
+        match event.kind {
+            EventKind::Corrupted => println!(
+                "{:>8}  {:>10}  {:<10}  {:<7}  {:>12}  {:?}",
+                event.offset, position, "corrupted", "", "", text
+            ),
+            EventKind::Instruction {
+                enabled,
+                contribution,
+                ..
+            } => println!(
+                "{:>8}  {:>10}  {:<10}  {:<7}  {:>12}  {}",
+                event.offset,
+                position,
+                "instr",
+                if enabled { "yes" } else { "no" },
+                contribution,
+                text
+            ),
+        }
+    }
+}
+
+// The original text with corrupted regions dimmed, do() and don't() in cyan,
+// and other instructions in green when they ran or red when disabled
+pub fn print_highlighted(contents: &[u8], events: &[Event]) {
+    let mut output = String::new();
+
+    for event in events {
+        let colour = match event.kind {
+            EventKind::Corrupted => DIM,
+            EventKind::Instruction {
+                effect: Effect::Enable | Effect::Disable,
+                ..
+            } => CYAN,
+            EventKind::Instruction { enabled: true, .. } => GREEN,
+            EventKind::Instruction { enabled: false, .. } => RED,
+        };
+
+        output.push_str(colour);
+        output.push_str(&text(contents, event));
+        output.push_str(RESET);
+    }
+
+    println!("{}", output);
+}
@@ -0,0 +1,165 @@
+use crate::letters::{letters, Letter, MatchOptions};
+use crate::point::{Direction, Point};
+
+// A rectangular grid stored row by row. All access is bounds checked, so
+// walking off any edge gives None instead of panicking, unless the grid wraps
+// around as a torus, in which case it continues from the opposite edge.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+    wrap: bool,
+}
+
+impl<T> Grid<T> {
+    pub fn new(rows: Vec<Vec<T>>) -> Result<Grid<T>, String> {
+        let height = rows.len();
+        let width = rows.first().map_or(0, |row| row.len());
+        let mut cells: Vec<T> = Vec::with_capacity(width * height);
+
+        for (y, row) in rows.into_iter().enumerate() {
+            if row.len() != width {
+                return Err(format!(
+                    "Row {} has {} cells but row 1 has {}, the grid must be rectangular",
+                    y + 1,
+                    row.len(),
+                    width
+                ));
+            }
+
+            cells.extend(row);
+        }
+
+        Ok(Grid {
+            width,
+            height,
+            cells,
+            wrap: false,
+        })
+    }
+
+    pub fn wrapping(mut self, wrap: bool) -> Self {
+        self.wrap = wrap;
+        self
+    }
+
+    pub fn wraps(&self) -> bool {
+        self.wrap
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn contains(&self, point: Point) -> bool {
+        point.x < self.width && point.y < self.height
+    }
+
+    pub fn get(&self, point: Point) -> Option<&T> {
+        if !self.contains(point) {
+            return None;
+        }
+
+        self.cells.get(point.y * self.width + point.x)
+    }
+
+    // The point dx right and dy down, or None if that is off the grid
+    pub fn translate(&self, point: Point, dx: usize, dy: usize) -> Option<Point> {
+        if self.wrap && self.contains(point) {
+            return Some(Point {
+                x: (point.x + dx) % self.width,
+                y: (point.y + dy) % self.height,
+            });
+        }
+
+        Some(Point {
+            x: point.x.checked_add(dx)?,
+            y: point.y.checked_add(dy)?,
+        })
+        .filter(|&next| self.contains(next))
+    }
+
+    // The next point in a direction, or None at the edge of a grid that
+    // doesn't wrap
+    pub fn step(&self, point: Point, direction: Direction) -> Option<Point> {
+        if self.wrap && self.contains(point) {
+            let (dx, dy) = direction.offset();
+
+            return Some(Point {
+                x: (point.x as isize + dx).rem_euclid(self.width as isize) as usize,
+                y: (point.y as isize + dy).rem_euclid(self.height as isize) as usize,
+            });
+        }
+
+        point.step(direction).filter(|&next| self.contains(next))
+    }
+
+    // Every point, row by row
+    pub fn points(&self) -> impl Iterator<Item = Point> + '_ {
+        (0..self.height).flat_map(move |y| (0..self.width).map(move |x| Point { x, y }))
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        // chunks panics on a zero size, which an empty grid would ask for
+        self.cells.chunks(self.width.max(1))
+    }
+
+    // The neighbours of a point that are inside the grid
+    pub fn neighbours(&self, point: Point) -> impl Iterator<Item = (Direction, Point)> + '_ {
+        Direction::ALL
+            .into_iter()
+            .filter_map(move |direction| Some((direction, self.step(point, direction)?)))
+    }
+
+    // The points from start (inclusive) to the edge of the grid in a direction.
+    // On a wrapping grid the ray never ends and revisits points.
+    pub fn ray(&self, start: Point, direction: Direction) -> impl Iterator<Item = Point> + '_ {
+        let first = Some(start).filter(|&start| self.contains(start));
+        std::iter::successors(first, move |&point| self.step(point, direction))
+    }
+}
+
+impl Grid<Letter> {
+    // Blank lines at the end of the text aren't part of the grid
+    pub fn parse(text: &str, options: MatchOptions) -> Result<Grid<Letter>, String> {
+        let mut lines: Vec<&str> = text.lines().collect();
+        while lines.last().is_some_and(|line| line.is_empty()) {
+            lines.pop();
+        }
+
+        Grid::new(
+            lines
+                .into_iter()
+                .map(|line| letters(line, options))
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ignores_trailing_blank_lines() {
+        let grid = Grid::parse("ab\ncd\n\n\n", MatchOptions::default()).unwrap();
+
+        assert_eq!((grid.width(), grid.height()), (2, 2));
+    }
+
+    #[test]
+    fn rejects_ragged_rows() {
+        assert_eq!(
+            Grid::parse("ab\nc\n", MatchOptions::default())
+                .err()
+                .unwrap(),
+            "Row 2 has 1 cells but row 1 has 2, the grid must be rectangular"
+        );
+        assert!(Grid::parse("ab\n\ncd\n", MatchOptions::default()).is_err());
+    }
+}
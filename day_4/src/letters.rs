@@ -0,0 +1,50 @@
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+use unicode_segmentation::UnicodeSegmentation;
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MatchOptions {
+    pub ignore_case: bool,
+    pub ignore_diacritics: bool,
+}
+
+// One user-perceived character (a grapheme cluster) of the grid or a word.
+// Letters match when their keys are equal, so é typed precomposed or as e
+// plus a combining accent is the same letter.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Letter {
+    pub text: String,
+    pub key: String,
+}
+
+fn key(grapheme: &str, options: MatchOptions) -> String {
+    let key: String = if options.ignore_diacritics {
+        grapheme
+            .nfd()
+            .filter(|&c| !is_combining_mark(c))
+            .nfc()
+            .collect()
+    } else {
+        grapheme.nfc().collect()
+    };
+
+    if options.ignore_case {
+        key.to_lowercase()
+    } else {
+        key
+    }
+}
+
+pub fn letters(text: &str, options: MatchOptions) -> Vec<Letter> {
+    text.graphemes(true)
+        .map(|grapheme| Letter {
+            text: grapheme.to_string(),
+            key: key(grapheme, options),
+        })
+        .collect()
+}
+
+// The length of a word in letters rather than bytes or chars
+pub fn letter_count(text: &str) -> usize {
+    text.graphemes(true).count()
+}
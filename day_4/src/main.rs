@@ -1,231 +1,196 @@
+mod grid;
+mod letters;
+mod multi_search;
+mod point;
+mod render;
+mod search;
+mod template;
+
+use grid::Grid;
+use letters::MatchOptions;
+use multi_search::find_words_aho_corasick;
+use search::{find_words, sort_matches};
 use std::env;
 use std::fs;
+use template::{find_templates, Template};
 
 const WORD: &str = "XMAS";
-
-#[derive(Clone)]
-struct Point {
-    x: usize,
-    y: usize,
+// Two MAS crossing on their A, each readable either way
+const X_MAS: &str = "M.S\n.A.\nM.S";
+
+fn usage(program: &str) -> ! {
+    eprintln!(
+        "Usage: {} --input <filename> [--words <word,word,...>] [--words-file <filename>] \
+         [--aho-corasick] [--template <filename> [--transforms]] [--render <ansi|svg|html> [--dots]] [--wrap] \
+         [--ignore-case] [--ignore-diacritics]",
+        program
+    );
+    std::process::exit(1);
 }
 
-#[derive(Debug)]
-enum Direction {
-    Up,
-    Down,
-    Left,
-    Right,
-    UpLeft,
-    UpRight,
-    DownLeft,
-    DownRight,
+// One word per line, ignoring blank lines
+fn read_words(filename: &str) -> Vec<String> {
+    fs::read_to_string(filename)
+        .expect("Something went wrong reading the words file")
+        .lines()
+        .map(|line| line.trim().to_string())
+        .filter(|word| !word.is_empty())
+        .collect()
 }
 
-fn update_point(point: &mut Point, direction: &Direction) {
-    match direction {
-        Direction::Up => {
-            point.y -= 1;
-        }
-        Direction::Down => {
-            point.y += 1;
-        }
-        Direction::Left => {
-            point.x -= 1;
-        }
-        Direction::Right => {
-            point.x += 1;
-        }
-        Direction::UpLeft => {
-            point.x -= 1;
-            point.y -= 1;
-        }
-        Direction::UpRight => {
-            point.x += 1;
-            point.y -= 1;
-        }
-        Direction::DownLeft => {
-            point.x -= 1;
-            point.y += 1;
-        }
-        Direction::DownRight => {
-            point.x += 1;
-            point.y += 1;
-        }
-    }
-}
+fn main() {
+    let args: Vec<String> = env::args().collect();
 
-fn check_direction(
-    grid: &Vec<Vec<char>>,
-    point: &Point,
-    direction: &Direction,
-    letter: char,
-) -> bool {
-    match direction {
-        Direction::Up => {
-            if point.y == 0 {
-                return false;
-            }
-            return grid[point.y - 1][point.x] == letter;
-        }
-        Direction::Down => {
-            if point.y == grid.len() - 1 {
-                return false;
-            }
-            return grid[point.y + 1][point.x] == letter;
-        }
-        Direction::Left => {
-            if point.x == 0 {
-                return false;
+    let mut filename: Option<String> = None;
+    let mut words: Vec<String> = Vec::new();
+    let mut aho_corasick = false;
+    let mut template_file: Option<String> = None;
+    let mut transforms = false;
+    let mut render: Option<String> = None;
+    let mut dots = false;
+    let mut wrap = false;
+    let mut options = MatchOptions::default();
+
+    // --input <filename> plus the options in usage
+    let mut i = 1;
+    while i < args.len() {
+        match (args[i].as_str(), args.get(i + 1)) {
+            ("--aho-corasick", _) => {
+                aho_corasick = true;
+                i += 1;
+                continue;
             }
-            return grid[point.y][point.x - 1] == letter;
-        }
-        Direction::Right => {
-            if point.x == grid[0].len() - 1 {
-                return false;
+            ("--transforms", _) => {
+                transforms = true;
+                i += 1;
+                continue;
             }
-            return grid[point.y][point.x + 1] == letter;
-        }
-        Direction::UpLeft => {
-            if point.y == 0 || point.x == 0 {
-                return false;
+            ("--ignore-case", _) => {
+                options.ignore_case = true;
+                i += 1;
+                continue;
             }
-            return grid[point.y - 1][point.x - 1] == letter;
-        }
-        Direction::UpRight => {
-            if point.y == 0 || point.x == grid[0].len() - 1 {
-                return false;
+            ("--ignore-diacritics", _) => {
+                options.ignore_diacritics = true;
+                i += 1;
+                continue;
             }
-            return grid[point.y - 1][point.x + 1] == letter;
-        }
-        Direction::DownLeft => {
-            if point.y == grid.len() - 1 || point.x == 0 {
-                return false;
+            ("--wrap", _) => {
+                wrap = true;
+                i += 1;
+                continue;
             }
-            return grid[point.y + 1][point.x - 1] == letter;
-        }
-        Direction::DownRight => {
-            if point.y == grid.len() - 1 || point.x == grid[0].len() - 1 {
-                return false;
+            ("--dots", _) => {
+                dots = true;
+                i += 1;
+                continue;
             }
-            return grid[point.y + 1][point.x + 1] == letter;
-        }
+            ("--render", Some(value)) => render = Some(value.clone()),
+            ("--template", Some(value)) => template_file = Some(value.clone()),
+            ("--input", Some(value)) => filename = Some(value.clone()),
+            ("--words", Some(value)) => words.extend(
+                value
+                    .split(',')
+                    .map(|word| word.trim().to_string())
+                    .filter(|word| !word.is_empty()),
+            ),
+            ("--words-file", Some(value)) => words.extend(read_words(value)),
+            _ => usage(&args[0]),
+        }
+        i += 2;
     }
-}
 
-fn main() {
-    let args: Vec<String> = env::args().collect();
+    let Some(filename) = filename else {
+        usage(&args[0]);
+    };
 
-    // --input <filename>
-    if args.len() != 3 {
-        eprintln!("Usage: {} --input <filename>", args[0]);
-        std::process::exit(1);
-    }
+    let contents = fs::read_to_string(&filename).expect("Something went wrong reading the file");
+
+    let grid = Grid::parse(&contents, options)
+        .unwrap_or_else(|e| {
+            eprintln!("Invalid grid in {}: {}", filename, e);
+            std::process::exit(1);
+        })
+        .wrapping(wrap);
 
-    if args[1] != "--input" {
-        eprintln!("Usage: {} --input <filename>", args[0]);
+    // Lines through a wrapping grid have no ends to search between
+    if wrap && aho_corasick {
+        eprintln!("--aho-corasick doesn't support --wrap");
         std::process::exit(1);
     }
 
-    let filename = &args[2];
-    let contents = fs::read_to_string(filename).expect("Something went wrong reading the file");
-
-    let mut grid: Vec<Vec<char>> = Vec::new();
-    for line in contents.lines() {
-        let mut row: Vec<char> = Vec::new();
-        for c in line.chars() {
-            row.push(c);
-        }
-        grid.push(row);
-    }
+    if let Some(template_file) = template_file {
+        let template = Template::from_file(&template_file, options).unwrap_or_else(|e| {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        });
 
-    let mut part_1 = 0;
+        let variants = if transforms {
+            template.variants()
+        } else {
+            vec![template]
+        };
 
-    for y in 0..grid.len() {
-        for x in 0..grid[y].len() {
-            if grid[y][x] != WORD.chars().nth(0).unwrap() {
-                continue;
-            }
+        let matches = find_templates(&grid, &variants);
 
-            let point = Point { x, y };
-
-            // Check all directions
-            for direction in vec![
-                Direction::Up,
-                Direction::Down,
-                Direction::Left,
-                Direction::Right,
-                Direction::UpLeft,
-                Direction::UpRight,
-                Direction::DownLeft,
-                Direction::DownRight,
-            ] {
-                let mut letter_point = point.clone();
-                let mut word_index = 1;
-                let mut found_direction = false;
-
-                while word_index < WORD.len() {
-                    found_direction = check_direction(
-                        &grid,
-                        &letter_point,
-                        &direction,
-                        WORD.chars().nth(word_index).unwrap(),
-                    );
-
-                    if !found_direction {
-                        break;
-                    }
-
-                    word_index += 1;
-                    update_point(&mut letter_point, &direction);
-                }
-
-                if found_direction {
-                    part_1 += 1;
-                }
-            }
+        for m in &matches {
+            println!("({}, {}) variant {}", m.top_left.x, m.top_left.y, m.variant);
         }
-    }
 
-    println!("Part 1: {}", part_1);
+        println!("Matches: {}", matches.len());
+        return;
+    }
 
-    let mut part_2 = 0;
+    if !words.is_empty() || render.is_some() {
+        if words.is_empty() {
+            words.push(WORD.to_string());
+        }
 
-    for y in 1..grid.len() - 1 {
-        for x in 1..grid[y].len() - 1 {
-            if grid[y][x] != 'A' {
-                continue;
-            }
+        // A word listed twice would otherwise report every match twice
+        let mut seen = std::collections::HashSet::new();
+        words.retain(|word| seen.insert(word.clone()));
 
-            let mut found_left_right = false;
-            let mut found_right_left = false;
+        let matches = if aho_corasick {
+            let mut matches = find_words_aho_corasick(&grid, &words, options);
+            sort_matches(&mut matches, &words);
+            matches
+        } else {
+            find_words(&grid, &words, options)
+        };
 
-            let point = Point { x, y };
+        if let Some(format) = render {
+            let output = match format.as_str() {
+                "ansi" => render::render_ansi(&grid, &matches, dots),
+                "svg" => render::render_svg(&grid, &matches, dots),
+                "html" => render::render_html(&grid, &matches, dots),
+                _ => usage(&args[0]),
+            };
 
-            if check_direction(&grid, &point, &Direction::UpLeft, 'M')
-                && check_direction(&grid, &point, &Direction::DownRight, 'S')
-            {
-                found_left_right = true;
-            } else if check_direction(&grid, &point, &Direction::UpLeft, 'S')
-                && check_direction(&grid, &point, &Direction::DownRight, 'M')
-            {
-                found_left_right = true;
-            }
+            print!("{}", output);
+            return;
+        }
 
-            if check_direction(&grid, &point, &Direction::UpRight, 'M')
-                && check_direction(&grid, &point, &Direction::DownLeft, 'S')
-            {
-                found_right_left = true;
-            } else if check_direction(&grid, &point, &Direction::UpRight, 'S')
-                && check_direction(&grid, &point, &Direction::DownLeft, 'M')
-            {
-                found_right_left = true;
-            }
+        for m in &matches {
+            println!(
+                "{} at ({}, {}) {:?}",
+                m.word, m.start.x, m.start.y, m.direction
+            );
+        }
 
-            if found_left_right && found_right_left {
-                part_2 += 1;
-            }
+        for word in &words {
+            let count = matches.iter().filter(|m| &m.word == word).count();
+            println!("{}: {}", word, count);
         }
+
+        return;
     }
 
+    let part_1 = find_words(&grid, &[WORD.to_string()], options).len();
+
+    println!("Part 1: {}", part_1);
+
+    let x_mas = Template::parse(X_MAS, options).unwrap();
+    let part_2 = find_templates(&grid, &x_mas.variants()).len();
+
     println!("Part 2: {}", part_2);
 }
@@ -0,0 +1,206 @@
+use crate::grid::Grid;
+use crate::letters::{letters, Letter, MatchOptions};
+use crate::point::{Direction, Point};
+use crate::search::{is_letter, Match};
+use aho_corasick::AhoCorasick;
+
+// A straight run of cells through the grid, read in one direction
+struct Line {
+    // The keys of the letters along the line, so the automaton matches them
+    // the same way find_words compares letters
+    text: String,
+    // Byte offset in text of each cell's key, with the cell it came from
+    cells: Vec<(usize, Point)>,
+    direction: Direction,
+}
+
+fn line(grid: &Grid<Letter>, points: Vec<Point>, direction: Direction) -> Line {
+    let mut text = String::new();
+    let mut cells: Vec<(usize, Point)> = Vec::new();
+
+    for point in points {
+        if let Some(letter) = grid.get(point) {
+            cells.push((text.len(), point));
+            text.push_str(&letter.key);
+        }
+    }
+
+    Line {
+        text,
+        cells,
+        direction,
+    }
+}
+
+// Every row, column and diagonal of the grid, each in both orientations
+fn lines(grid: &Grid<Letter>) -> Vec<Line> {
+    let (width, height) = (grid.width(), grid.height());
+    let mut starts: Vec<(Point, Direction, Direction)> = Vec::new();
+
+    for y in 0..height {
+        starts.push((Point { x: 0, y }, Direction::Right, Direction::Left));
+        starts.push((Point { x: 0, y }, Direction::DownRight, Direction::UpLeft));
+        starts.push((
+            Point {
+                x: width.saturating_sub(1),
+                y,
+            },
+            Direction::DownLeft,
+            Direction::UpRight,
+        ));
+    }
+
+    for x in 0..width {
+        starts.push((Point { x, y: 0 }, Direction::Down, Direction::Up));
+
+        // The diagonals starting in the top left and right corners were
+        // already added from the sides
+        if x > 0 {
+            starts.push((Point { x, y: 0 }, Direction::DownRight, Direction::UpLeft));
+        }
+        if x + 1 < width {
+            starts.push((Point { x, y: 0 }, Direction::DownLeft, Direction::UpRight));
+        }
+    }
+
+    let mut lines: Vec<Line> = Vec::new();
+
+    for (start, direction, opposite) in starts {
+        let mut points: Vec<Point> = grid.ray(start, direction).collect();
+        lines.push(line(grid, points.clone(), direction));
+        points.reverse();
+        lines.push(line(grid, points, opposite));
+    }
+
+    lines
+}
+
+// The same matches as find_words, found by running one automaton for all the
+// words over each line instead of trying every word from every cell, which
+// scales to thousands of words on large grids
+pub fn find_words_aho_corasick(
+    grid: &Grid<Letter>,
+    words: &[String],
+    options: MatchOptions,
+) -> Vec<Match> {
+    let words: Vec<(&String, Vec<Letter>)> = words
+        .iter()
+        .filter(|word| !word.is_empty())
+        .map(|word| (word, letters(word, options)))
+        .collect();
+    if words.is_empty() {
+        return Vec::new();
+    }
+
+    let keys: Vec<String> = words
+        .iter()
+        .map(|(_, letters)| letters.iter().map(|l| l.key.as_str()).collect())
+        .collect();
+    let automaton = AhoCorasick::new(&keys).expect("Could not build the automaton");
+    let mut matches: Vec<Match> = Vec::new();
+
+    for line in lines(grid) {
+        for found in automaton.find_overlapping_iter(&line.text) {
+            let (word, letters) = &words[found.pattern().as_usize()];
+
+            // Single letters are reported once per cell, like find_words does
+            if letters.len() == 1 && line.direction != Direction::Right {
+                continue;
+            }
+
+            // Keys can run together, so a match of the concatenated text only
+            // counts if it lines up with whole letters
+            let Ok(cell) = line
+                .cells
+                .binary_search_by_key(&found.start(), |&(offset, _)| offset)
+            else {
+                continue;
+            };
+
+            let cells = &line.cells[cell..];
+            if cells.len() < letters.len()
+                || !cells
+                    .iter()
+                    .zip(letters)
+                    .all(|(&(_, point), letter)| is_letter(grid, point, letter))
+            {
+                continue;
+            }
+
+            matches.push(Match {
+                word: word.to_string(),
+                start: line.cells[cell].1,
+                direction: line.direction,
+            });
+        }
+    }
+
+    matches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::{find_words, sort_matches};
+
+    fn words(list: &[&str]) -> Vec<String> {
+        list.iter().map(|w| w.to_string()).collect()
+    }
+
+    fn assert_same_matches(file: &str, word_lists: &[Vec<String>]) {
+        let contents =
+            std::fs::read_to_string(format!("{}/{}", env!("CARGO_MANIFEST_DIR"), file)).unwrap();
+
+        for (ignore_case, ignore_diacritics) in
+            [(false, false), (true, false), (false, true), (true, true)]
+        {
+            let options = MatchOptions {
+                ignore_case,
+                ignore_diacritics,
+            };
+            let grid = Grid::parse(&contents, options).unwrap();
+
+            for words in word_lists {
+                let mut matches = find_words_aho_corasick(&grid, words, options);
+                sort_matches(&mut matches, words);
+                assert!(!matches.is_empty(), "{} with {:?}", file, words);
+
+                assert_eq!(
+                    matches,
+                    find_words(&grid, words, options),
+                    "{} with {:?} and {:?}",
+                    file,
+                    words,
+                    options
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn same_matches_as_cell_search() {
+        assert_same_matches(
+            "input-test.txt",
+            &[
+                words(&["XMAS"]),
+                words(&["X", "M", "A", "S"]),
+                words(&["XMAS", "MAS", "AS", "SAMX", "XMASAMX"]),
+                words(&["MAM", "SAS", "XMX", "MM", "AMA"]),
+                words(&["xmas", "s", "MA"]),
+            ],
+        );
+    }
+
+    #[test]
+    fn same_matches_on_unicode_grid() {
+        assert_same_matches(
+            "input-unicode-test.txt",
+            &[
+                words(&["CAFÉ", "café", "cafe"]),
+                words(&["É", "e", "🇫🇷", "!"]),
+                words(&["été", "ée", "éé", "ete"]),
+                words(&["CAF", "CAFÉ!", "AFA", "faf"]),
+            ],
+        );
+    }
+}
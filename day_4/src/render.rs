@@ -0,0 +1,178 @@
+use crate::grid::Grid;
+use crate::letters::{letter_count, Letter};
+use crate::point::{Direction, Point};
+use crate::search::Match;
+use std::collections::HashMap;
+
+const RESET: &str = "\x1b[0m";
+const DIM: &str = "\x1b[2m";
+// Bold foreground colours, cycled through by word
+const ANSI_COLOURS: [&str; 6] = [
+    "\x1b[1;31m",
+    "\x1b[1;32m",
+    "\x1b[1;33m",
+    "\x1b[1;34m",
+    "\x1b[1;35m",
+    "\x1b[1;36m",
+];
+const SVG_COLOURS: [&str; 6] = [
+    "#e6194b", "#3cb44b", "#ffe119", "#4363d8", "#f58231", "#911eb4",
+];
+const CELL_SIZE: usize = 24;
+
+// The cells a match covers, in reading order
+pub fn cells(grid: &Grid<Letter>, m: &Match) -> Vec<Point> {
+    grid.ray(m.start, m.direction)
+        .take(letter_count(&m.word))
+        .collect()
+}
+
+// The colour index of each word, in the order their first matches appear
+fn word_colours(matches: &[Match]) -> HashMap<&str, usize> {
+    let mut colours: HashMap<&str, usize> = HashMap::new();
+
+    for m in matches {
+        let next = colours.len();
+        colours.entry(m.word.as_str()).or_insert(next);
+    }
+
+    colours
+}
+
+// The colour index of the first match covering each cell
+fn covered_cells(grid: &Grid<Letter>, matches: &[Match]) -> HashMap<Point, usize> {
+    let colours = word_colours(matches);
+    let mut covered: HashMap<Point, usize> = HashMap::new();
+
+    for m in matches {
+        for cell in cells(grid, m) {
+            covered.entry(cell).or_insert(colours[m.word.as_str()]);
+        }
+    }
+
+    covered
+}
+
+// The grid with matched letters coloured by word. Other letters are dimmed,
+// or replaced with '.' like the puzzle's example when dots is set.
+pub fn render_ansi(grid: &Grid<Letter>, matches: &[Match], dots: bool) -> String {
+    let covered = covered_cells(grid, matches);
+    let mut output = String::new();
+
+    for (y, row) in grid.rows().enumerate() {
+        for (x, letter) in row.iter().enumerate() {
+            match covered.get(&Point { x, y }) {
+                Some(colour) => {
+                    output.push_str(ANSI_COLOURS[colour % ANSI_COLOURS.len()]);
+                    output.push_str(&letter.text);
+                    output.push_str(RESET);
+                }
+                None if dots => output.push('.'),
+                None => {
+                    output.push_str(DIM);
+                    output.push_str(&letter.text);
+                    output.push_str(RESET);
+                }
+            }
+        }
+        output.push('\n');
+    }
+
+    output
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn centre(index: usize) -> usize {
+    index * CELL_SIZE + CELL_SIZE / 2
+}
+
+// The straight runs of a match's cells as (first, last) pairs. A match only
+// has more than one when it wraps around the edge of the grid.
+fn segments(cells: &[Point], direction: Direction) -> Vec<(Point, Point)> {
+    let mut segments: Vec<(Point, Point)> = Vec::new();
+    let mut first = cells[0];
+
+    for pair in cells.windows(2) {
+        if pair[0].step(direction) != Some(pair[1]) {
+            segments.push((first, pair[0]));
+            first = pair[1];
+        }
+    }
+
+    segments.push((first, cells[cells.len() - 1]));
+    segments
+}
+
+// The grid as an SVG with a line drawn through the letters of each match
+pub fn render_svg(grid: &Grid<Letter>, matches: &[Match], dots: bool) -> String {
+    let covered = covered_cells(grid, matches);
+    let colours = word_colours(matches);
+    let width = grid.width() * CELL_SIZE;
+    let height = grid.height() * CELL_SIZE;
+
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{1}\" \
+         viewBox=\"0 0 {0} {1}\" font-family=\"monospace\" font-size=\"{2}\">\n",
+        width,
+        height,
+        CELL_SIZE * 2 / 3
+    );
+    svg.push_str(&format!(
+        "  <rect width=\"{}\" height=\"{}\" fill=\"white\"/>\n",
+        width, height
+    ));
+
+    for m in matches {
+        let colour = SVG_COLOURS[colours[m.word.as_str()] % SVG_COLOURS.len()];
+
+        for (first, last) in segments(&cells(grid, m), m.direction) {
+            svg.push_str(&format!(
+                "  <line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"{}\" \
+             stroke-width=\"{}\" stroke-linecap=\"round\" stroke-opacity=\"0.4\"/>\n",
+                centre(first.x),
+                centre(first.y),
+                centre(last.x),
+                centre(last.y),
+                colour,
+                CELL_SIZE * 2 / 3
+            ));
+        }
+    }
+
+    for (y, row) in grid.rows().enumerate() {
+        for (x, letter) in row.iter().enumerate() {
+            let matched = covered.contains_key(&Point { x, y });
+            let (text, fill) = match (matched, dots) {
+                (true, _) => (letter.text.as_str(), "black"),
+                (false, true) => (".", "#bbbbbb"),
+                (false, false) => (letter.text.as_str(), "#bbbbbb"),
+            };
+
+            svg.push_str(&format!(
+                "  <text x=\"{}\" y=\"{}\" fill=\"{}\" text-anchor=\"middle\" \
+                 dominant-baseline=\"central\">{}</text>\n",
+                centre(x),
+                centre(y),
+                fill,
+                escape(text)
+            ));
+        }
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+pub fn render_html(grid: &Grid<Letter>, matches: &[Match], dots: bool) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Word search</title>\n\
+         </head>\n<body>\n{}</body>\n</html>\n",
+        render_svg(grid, matches, dots)
+    )
+}
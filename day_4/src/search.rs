@@ -0,0 +1,169 @@
+use crate::grid::Grid;
+use crate::letters::{letters, Letter, MatchOptions};
+use crate::point::{Direction, Point};
+use std::collections::HashSet;
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Match {
+    pub word: String,
+    pub start: Point,
+    pub direction: Direction,
+}
+
+// The cells the word covers reading from point in a direction, if it is there
+fn matches_from(
+    grid: &Grid<Letter>,
+    point: Point,
+    direction: Direction,
+    word: &[Letter],
+) -> Option<Vec<Point>> {
+    let points: Vec<Point> = grid.ray(point, direction).take(word.len()).collect();
+
+    // On a wrapping grid smaller than the word the ray comes back around, and
+    // a word reusing one of its own cells doesn't count
+    if grid.wraps() && (1..points.len()).any(|i| points[..i].contains(&points[i])) {
+        return None;
+    }
+
+    let found = points.len() == word.len()
+        && points
+            .iter()
+            .zip(word)
+            .all(|(&p, letter)| is_letter(grid, p, letter));
+
+    Some(points).filter(|_| found)
+}
+
+pub fn is_letter(grid: &Grid<Letter>, point: Point, letter: &Letter) -> bool {
+    grid.get(point).is_some_and(|cell| cell.key == letter.key)
+}
+
+// Every occurrence of every word in the grid, read in any of the eight
+// directions. A single letter word reads the same every way, so it is only
+// reported once per cell, reading Right. On a narrow wrapping grid several
+// directions can walk the same cells, and those count as one match.
+pub fn find_words(grid: &Grid<Letter>, words: &[String], options: MatchOptions) -> Vec<Match> {
+    let words: Vec<(&String, Vec<Letter>)> = words
+        .iter()
+        .filter(|word| !word.is_empty())
+        .map(|word| (word, letters(word, options)))
+        .collect();
+
+    let mut matches: Vec<Match> = Vec::new();
+    let mut seen: HashSet<(&String, Vec<Point>)> = HashSet::new();
+
+    for point in grid.points() {
+        for (word, letters) in &words {
+            if !is_letter(grid, point, &letters[0]) {
+                continue;
+            }
+
+            // Only directions whose neighbour has the second letter can match
+            let directions: Vec<Direction> = if letters.len() == 1 {
+                vec![Direction::Right]
+            } else {
+                grid.neighbours(point)
+                    .filter(|&(_, next)| is_letter(grid, next, &letters[1]))
+                    .map(|(direction, _)| direction)
+                    .collect()
+            };
+
+            for direction in directions {
+                let Some(points) = matches_from(grid, point, direction, letters) else {
+                    continue;
+                };
+
+                if seen.insert((*word, points)) {
+                    matches.push(Match {
+                        word: word.to_string(),
+                        start: point,
+                        direction,
+                    });
+                }
+            }
+        }
+    }
+
+    matches
+}
+
+// Puts matches in the order find_words reports them: by cell, then by the
+// word's position in the list, then by direction
+pub fn sort_matches(matches: &mut [Match], words: &[String]) {
+    let direction_index =
+        |direction: &Direction| Direction::ALL.iter().position(|d| d == direction).unwrap();
+
+    matches.sort_by_key(|m| {
+        (
+            m.start.y,
+            m.start.x,
+            words.iter().position(|w| w == &m.word),
+            direction_index(&m.direction),
+        )
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wrapped(text: &str) -> Grid<Letter> {
+        Grid::parse(text, MatchOptions::default())
+            .unwrap()
+            .wrapping(true)
+    }
+
+    fn count(grid: &Grid<Letter>, word: &str) -> usize {
+        find_words(grid, &[word.to_string()], MatchOptions::default()).len()
+    }
+
+    #[test]
+    fn single_row_counts_each_cell_sequence_once() {
+        let grid = wrapped("XMAS");
+
+        assert_eq!(count(&grid, "XMAS"), 1);
+        assert_eq!(count(&grid, "SAMX"), 1);
+        assert_eq!(count(&grid, "ASXM"), 1);
+    }
+
+    #[test]
+    fn two_cells_read_once_each_way() {
+        let grid = wrapped("AB");
+
+        assert_eq!(count(&grid, "AB"), 1);
+        assert_eq!(count(&grid, "BA"), 1);
+    }
+
+    #[test]
+    fn word_longer_than_grid_does_not_reuse_cells() {
+        let grid = wrapped("XMA");
+
+        assert_eq!(count(&grid, "XMAX"), 0);
+    }
+
+    #[test]
+    fn square_grid_reads_across_every_edge() {
+        // Every row, column and diagonal of a Latin square holds each letter
+        // once, so reading with wrap-around finds distinct lines
+        let grid = wrapped("ABC\nBCA\nCAB");
+        let matches = find_words(&grid, &["ABC".to_string()], MatchOptions::default());
+
+        let cells: HashSet<Vec<Point>> = matches
+            .iter()
+            .map(|m| grid.ray(m.start, m.direction).take(3).collect())
+            .collect();
+
+        assert_eq!(cells.len(), matches.len());
+        assert!(matches
+            .iter()
+            .any(|m| m.start == Point { x: 1, y: 2 } && m.direction == Direction::Right));
+    }
+
+    #[test]
+    fn unwrapped_grid_is_unchanged() {
+        let grid = Grid::parse("XMAS", MatchOptions::default()).unwrap();
+
+        assert_eq!(count(&grid, "XMAS"), 1);
+        assert_eq!(count(&grid, "MASX"), 0);
+    }
+}
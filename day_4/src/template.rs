@@ -0,0 +1,243 @@
+use crate::grid::Grid;
+use crate::letters::{letters, Letter, MatchOptions};
+use crate::point::Point;
+use crate::search::is_letter;
+use std::fs;
+
+// Marks a cell that matches any letter
+const WILDCARD: &str = ".";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Template {
+    // None is a wildcard
+    cells: Vec<Vec<Option<Letter>>>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TemplateMatch {
+    pub top_left: Point,
+    // Index into the variants that were searched for
+    pub variant: usize,
+}
+
+impl Template {
+    pub fn parse(text: &str, options: MatchOptions) -> Result<Template, String> {
+        let cells: Vec<Vec<Option<Letter>>> = text
+            .lines()
+            .filter(|line| !line.is_empty())
+            .map(|line| {
+                letters(line, options)
+                    .into_iter()
+                    .map(|letter| Some(letter).filter(|l| l.text != WILDCARD))
+                    .collect()
+            })
+            .collect();
+
+        if cells.is_empty() {
+            return Err("Template is empty".to_string());
+        }
+
+        if cells.iter().any(|row| row.len() != cells[0].len()) {
+            return Err("Template rows must all be the same length".to_string());
+        }
+
+        Ok(Template { cells })
+    }
+
+    pub fn from_file(filename: &str, options: MatchOptions) -> Result<Template, String> {
+        let contents = fs::read_to_string(filename)
+            .map_err(|e| format!("Could not read template {}: {}", filename, e))?;
+
+        Template::parse(&contents, options)
+    }
+
+    fn width(&self) -> usize {
+        self.cells[0].len()
+    }
+
+    fn height(&self) -> usize {
+        self.cells.len()
+    }
+
+    // A quarter turn clockwise
+    fn rotate(&self) -> Template {
+        let cells = (0..self.width())
+            .map(|x| {
+                (0..self.height())
+                    .rev()
+                    .map(|y| self.cells[y][x].clone())
+                    .collect()
+            })
+            .collect();
+
+        Template { cells }
+    }
+
+    // Mirrored left to right
+    fn reflect(&self) -> Template {
+        let cells = self
+            .cells
+            .iter()
+            .map(|row| row.iter().rev().cloned().collect())
+            .collect();
+
+        Template { cells }
+    }
+
+    // Whether two templates match the same cells, comparing letters by key
+    // rather than by how they were written
+    fn same_keys(&self, other: &Template) -> bool {
+        let key = |cell: &Option<Letter>| cell.as_ref().map(|letter| letter.key.clone());
+
+        self.cells.len() == other.cells.len()
+            && self
+                .cells
+                .iter()
+                .zip(&other.cells)
+                .all(|(a, b)| a.len() == b.len() && a.iter().zip(b).all(|(a, b)| key(a) == key(b)))
+    }
+
+    // The distinct templates among all rotations and reflections, starting
+    // with this one
+    pub fn variants(&self) -> Vec<Template> {
+        let mut variants: Vec<Template> = Vec::new();
+        let mut current = self.clone();
+
+        for _ in 0..4 {
+            for candidate in [current.clone(), current.reflect()] {
+                if !variants.iter().any(|v| v.same_keys(&candidate)) {
+                    variants.push(candidate);
+                }
+            }
+            current = current.rotate();
+        }
+
+        variants
+    }
+
+    fn matches_at(&self, grid: &Grid<Letter>, top_left: Point) -> bool {
+        // On a wrapping grid a template larger than the grid would cover some
+        // cells twice
+        if grid.wraps() && (self.width() > grid.width() || self.height() > grid.height()) {
+            return false;
+        }
+
+        self.cells.iter().enumerate().all(|(dy, row)| {
+            row.iter().enumerate().all(|(dx, cell)| match cell {
+                None => true,
+                Some(letter) => grid
+                    .translate(top_left, dx, dy)
+                    .is_some_and(|point| is_letter(grid, point, letter)),
+            })
+        })
+    }
+}
+
+// Every placement of each template in the grid. A placement matching several
+// variants is reported once per variant.
+pub fn find_templates(grid: &Grid<Letter>, variants: &[Template]) -> Vec<TemplateMatch> {
+    let mut matches: Vec<TemplateMatch> = Vec::new();
+
+    for top_left in grid.points() {
+        for (variant, template) in variants.iter().enumerate() {
+            if template.matches_at(grid, top_left) {
+                matches.push(TemplateMatch { top_left, variant });
+            }
+        }
+    }
+
+    matches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn count(grid: &str, template: &str, options: MatchOptions) -> usize {
+        let grid = Grid::parse(grid, options).unwrap();
+        let template = Template::parse(template, options).unwrap();
+
+        find_templates(&grid, &[template]).len()
+    }
+
+    #[test]
+    fn folds_case_and_diacritics() {
+        let ignore_case = MatchOptions {
+            ignore_case: true,
+            ..MatchOptions::default()
+        };
+        let ignore_diacritics = MatchOptions {
+            ignore_diacritics: true,
+            ..MatchOptions::default()
+        };
+
+        assert_eq!(count("xmas", "XM", MatchOptions::default()), 0);
+        assert_eq!(count("xmas", "XM", ignore_case), 1);
+        assert_eq!(count("cafe", "café", ignore_diacritics), 1);
+    }
+
+    #[test]
+    fn matches_either_normalization_form() {
+        assert_eq!(
+            count("cafe\u{301}", "caf\u{e9}", MatchOptions::default()),
+            1
+        );
+    }
+
+    // Counts every rotation and reflection, as --transforms does
+    fn count_variants(grid: &str, template: &str, wrap: bool) -> usize {
+        let grid = Grid::parse(grid, MatchOptions::default())
+            .unwrap()
+            .wrapping(wrap);
+        let template = Template::parse(template, MatchOptions::default()).unwrap();
+
+        find_templates(&grid, &template.variants()).len()
+    }
+
+    #[test]
+    fn matches_across_wrapped_edges() {
+        assert_eq!(count("BA", "AB", MatchOptions::default()), 0);
+        assert_eq!(count_variants("BA", "AB", false), 1);
+        assert_eq!(count_variants("BA", "AB", true), 2);
+        assert_eq!(count_variants("CD\nAB", "AB\nCD", true), 4);
+    }
+
+    #[test]
+    fn template_larger_than_wrapped_grid_never_matches() {
+        // Without the size check both A cells of the template would land on
+        // the same grid cell
+        assert_eq!(count_variants("AA", "A.A", true), 0);
+        assert_eq!(count_variants("A\nA", "A\n.\nA", true), 0);
+        assert_eq!(count_variants("MAS\nAAA", "M.S\n.A.\nM.S", true), 0);
+    }
+
+    #[test]
+    fn x_mas_wraps() {
+        let contents =
+            std::fs::read_to_string(concat!(env!("CARGO_MANIFEST_DIR"), "/input-wrap-test.txt"))
+                .unwrap();
+        let x_mas = "M.S\n.A.\nM.S";
+
+        assert_eq!(count_variants(&contents, x_mas, false), 0);
+        assert_eq!(count_variants(&contents, x_mas, true), 2);
+    }
+
+    #[test]
+    fn variants_dedupe_by_key() {
+        let ignore_case = MatchOptions {
+            ignore_case: true,
+            ..MatchOptions::default()
+        };
+
+        // A reflection that only differs in case is the same template
+        let template = Template::parse("aA", ignore_case).unwrap();
+        assert_eq!(template.variants().len(), 2);
+        assert_eq!(
+            Template::parse("aA", MatchOptions::default())
+                .unwrap()
+                .variants()
+                .len(),
+            4
+        );
+    }
+}
@@ -0,0 +1,72 @@
+use crate::explain::find_violations;
+use std::collections::{BTreeSet, HashSet};
+
+const VIOLATED: &str = "color=red, penwidth=2";
+
+// Sorted, so the output is the same from run to run
+fn edges(rules: &HashSet<(u16, u16)>) -> BTreeSet<(u16, u16)> {
+    rules.iter().copied().collect()
+}
+
+fn edge_line(before: u16, after: u16, violated: bool) -> String {
+    if violated {
+        format!("  {} -> {} [{}];\n", before, after, VIOLATED)
+    } else {
+        format!("  {} -> {};\n", before, after)
+    }
+}
+
+// Every rule as an edge from the page that has to come first. Rules broken
+// by at least one update are drawn in red.
+pub fn rule_graph(rules: &HashSet<(u16, u16)>, updates: &[Vec<u16>]) -> String {
+    let violated: HashSet<(u16, u16)> = updates
+        .iter()
+        .flat_map(|update| find_violations(rules, update))
+        .map(|v| (v.before, v.after))
+        .collect();
+
+    let mut dot = String::from("digraph rules {\n");
+
+    for (before, after) in edges(rules) {
+        dot.push_str(&edge_line(
+            before,
+            after,
+            violated.contains(&(before, after)),
+        ));
+    }
+
+    dot.push_str("}\n");
+    dot
+}
+
+// The rules between one update's pages, with each page labelled by its
+// position and the rules the update breaks drawn in red
+pub fn update_graph(rules: &HashSet<(u16, u16)>, update: &[u16], number: usize) -> String {
+    let violated: HashSet<(u16, u16)> = find_violations(rules, update)
+        .into_iter()
+        .map(|v| (v.before, v.after))
+        .collect();
+    let pages: HashSet<u16> = update.iter().copied().collect();
+
+    let mut dot = format!("digraph update_{} {{\n", number);
+
+    for (position, page) in update.iter().enumerate() {
+        dot.push_str(&format!(
+            "  {} [label=\"{}\\n#{}\"];\n",
+            page, page, position
+        ));
+    }
+
+    for (before, after) in edges(rules) {
+        if pages.contains(&before) && pages.contains(&after) {
+            dot.push_str(&edge_line(
+                before,
+                after,
+                violated.contains(&(before, after)),
+            ));
+        }
+    }
+
+    dot.push_str("}\n");
+    dot
+}
@@ -0,0 +1,115 @@
+use crate::ordering::must_precede;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
+
+// A rule before|after broken by after appearing at after_position, earlier
+// in the update than before at before_position
+#[derive(Debug, Serialize)]
+pub struct Violation {
+    pub before: u16,
+    pub after: u16,
+    pub before_position: usize,
+    pub after_position: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Explanation {
+    pub update: usize,
+    pub pages: Vec<u16>,
+    pub violations: Vec<Violation>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RuleCount {
+    pub before: u16,
+    pub after: u16,
+    pub count: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Report {
+    pub invalid: Vec<Explanation>,
+    // Most violated first
+    pub summary: Vec<RuleCount>,
+}
+
+pub fn find_violations(rules: &HashSet<(u16, u16)>, update: &[u16]) -> Vec<Violation> {
+    let mut violations: Vec<Violation> = Vec::new();
+
+    for (after_position, &after) in update.iter().enumerate() {
+        for (before_position, &before) in update.iter().enumerate().skip(after_position + 1) {
+            if must_precede(rules, before, after) {
+                violations.push(Violation {
+                    before,
+                    after,
+                    before_position,
+                    after_position,
+                });
+            }
+        }
+    }
+
+    violations
+}
+
+// Updates are numbered from 1 in the order they appear in the input
+pub fn explain(rules: &HashSet<(u16, u16)>, updates: &[Vec<u16>]) -> Report {
+    let mut invalid: Vec<Explanation> = Vec::new();
+    let mut counts: BTreeMap<(u16, u16), usize> = BTreeMap::new();
+
+    for (i, update) in updates.iter().enumerate() {
+        let violations = find_violations(rules, update);
+        if violations.is_empty() {
+            continue;
+        }
+
+        for v in &violations {
+            *counts.entry((v.before, v.after)).or_default() += 1;
+        }
+
+        invalid.push(Explanation {
+            update: i + 1,
+            pages: update.clone(),
+            violations,
+        });
+    }
+
+    let mut summary: Vec<RuleCount> = counts
+        .into_iter()
+        .map(|((before, after), count)| RuleCount {
+            before,
+            after,
+            count,
+        })
+        .collect();
+    // Stable, so ties stay in rule order
+    summary.sort_by_key(|rule| std::cmp::Reverse(rule.count));
+
+    Report { invalid, summary }
+}
+
+pub fn print_text(report: &Report) {
+    for explanation in &report.invalid {
+        let pages: Vec<String> = explanation.pages.iter().map(|p| p.to_string()).collect();
+        println!("Update {}: {}", explanation.update, pages.join(","));
+
+        for v in &explanation.violations {
+            println!(
+                "  {}|{}: {} at position {} comes before {} at position {}",
+                v.before, v.after, v.after, v.after_position, v.before, v.before_position
+            );
+        }
+    }
+
+    println!("Most violated rules:");
+    for rule in &report.summary {
+        println!("  {}|{}  {}", rule.before, rule.after, rule.count);
+    }
+}
+
+pub fn print_json(report: &Report) {
+    println!(
+        "{}",
+        serde_json::to_string_pretty(report).expect("Could not serialise report")
+    );
+}
@@ -0,0 +1,8 @@
+pub mod dot;
+pub mod explain;
+pub mod ordering;
+pub mod orderings;
+pub mod parse;
+pub mod queue;
+pub mod repair;
+pub mod repl;
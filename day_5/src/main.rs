@@ -1,98 +1,279 @@
-use regex::Regex;
-use std::{cmp::Ordering, collections::HashMap, env, fs};
+use day_5::explain::find_violations;
+use day_5::ordering::order_update;
+use day_5::queue::PrintQueue;
+use day_5::{dot, explain, orderings, parse, repair, repl};
+use std::{collections::HashSet, env, fs, io};
 
-const MAP_REGEX: &str = r"([\d]+)\|([\d]+)";
-const UPDATE_REGEX: &str = r"(\d+?)(?:,|$)";
+fn check_valid(rules: &HashSet<(u16, u16)>, update: &[u16]) -> bool {
+    find_violations(rules, update).is_empty()
+}
+
+// Parsing already rejects anything the queue would, so this only fails on a bug
+fn print_queue(rules: HashSet<(u16, u16)>, updates: Vec<Vec<u16>>) -> PrintQueue {
+    PrintQueue::new(rules, updates).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    })
+}
 
-fn check_valid(page_map: &HashMap<u16, Vec<u16>>, update: &Vec<u16>) -> bool {
-    return update.is_sorted_by(|a, b| {
-        if !page_map.contains_key(a) {
-            return false;
+fn usage(program: &str) -> ! {
+    eprintln!(
+        "Usage: {} --input <filename> [--explain <text|json>] [--dot <all|update number>] [--repair] \
+         [--orderings [--list <count>]] [--repl]",
+        program
+    );
+    std::process::exit(1);
+}
+
+fn plural(count: usize, word: &str) -> String {
+    if count == 1 {
+        format!("{} {}", count, word)
+    } else {
+        format!("{} {}s", count, word)
+    }
+}
+
+fn join(pages: &[u16]) -> String {
+    let pages: Vec<String> = pages.iter().map(|p| p.to_string()).collect();
+    pages.join(",")
+}
+
+// The fewest moves and adjacent swaps that fix each invalid update
+fn print_repairs(rules: &HashSet<(u16, u16)>, updates: &[Vec<u16>]) {
+    let mut total_moves = 0;
+    let mut total_swaps = 0;
+
+    for (i, update) in updates.iter().enumerate() {
+        if check_valid(rules, update) {
+            continue;
         }
 
-        let page = page_map.get(a).unwrap();
+        let repair = repair::repair(rules, update).unwrap_or_else(|cycle| {
+            eprintln!("Update {}: {}", i + 1, cycle);
+            std::process::exit(1);
+        });
 
-        if !page.contains(b) {
-            return false;
+        println!("Update {}: {}", i + 1, join(update));
+
+        println!(
+            "  {} to {}",
+            plural(repair.moves.len(), "move"),
+            join(&repair.moved)
+        );
+        for m in &repair.moves {
+            println!(
+                "    move {} from position {} to position {}",
+                m.page, m.from, m.to
+            );
         }
+        total_moves += repair.moves.len();
 
-        return true;
-    });
+        match (&repair.swaps, &repair.swapped) {
+            (Some(swaps), Some(swapped)) => {
+                println!(
+                    "  {} to {}",
+                    plural(swaps.len(), "adjacent swap"),
+                    join(swapped)
+                );
+                for swap in swaps {
+                    println!(
+                        "    swap {} and {} at positions {} and {}",
+                        swap.left,
+                        swap.right,
+                        swap.position,
+                        swap.position + 1
+                    );
+                }
+                total_swaps += swaps.len();
+            }
+            _ => println!(
+                "  adjacent swaps not searched for updates of more than {} pages",
+                repair::MAX_SWAP_PAGES
+            ),
+        }
+    }
+
+    println!("Total moves: {}", total_moves);
+    println!("Total adjacent swaps: {}", total_swaps);
+}
+
+// How many orders satisfy the rules for each update, listing up to list of
+// them, so that rule sets which leave updates under-specified stand out
+fn print_orderings(rules: &HashSet<(u16, u16)>, updates: &[Vec<u16>], list: usize) {
+    let mut under_specified = 0;
+
+    for (i, update) in updates.iter().enumerate() {
+        let count = orderings::count_orderings(rules, update);
+
+        match &count {
+            Ok(count) => {
+                println!(
+                    "Update {}: {}  {} ordering{}",
+                    i + 1,
+                    join(update),
+                    count,
+                    if *count == 1 { "" } else { "s" }
+                );
+                if *count > 1 {
+                    under_specified += 1;
+                }
+            }
+            Err(limit) => println!("Update {}: {}  not counted, {}", i + 1, join(update), limit),
+        }
+
+        // Contradictory rules would send the search down every dead end
+        if list == 0 || order_update(rules, update).is_err() {
+            continue;
+        }
+
+        match orderings::list_orderings(rules, update, list) {
+            Ok(orders) => {
+                for order in orders {
+                    println!("  {}", join(&order));
+                }
+            }
+            Err(limit) => println!("  not listed, {}", limit),
+        }
+    }
+
+    println!(
+        "Under-specified updates: {} of {}",
+        under_specified,
+        updates.len()
+    );
 }
 
 fn main() {
     let args: Vec<String> = env::args().collect();
 
-    // --input <filename>
-    if args.len() != 3 {
-        eprintln!("Usage: {} --input <filename>", args[0]);
-        std::process::exit(1);
+    let mut filename: Option<String> = None;
+    let mut explain: Option<String> = None;
+    let mut dot: Option<String> = None;
+    let mut repair = false;
+    let mut orderings = false;
+    let mut list = 0;
+    let mut interactive = false;
+
+    // --input <filename> plus the options in usage
+    let mut i = 1;
+    while i < args.len() {
+        match (args[i].as_str(), args.get(i + 1)) {
+            ("--orderings", _) => {
+                orderings = true;
+                i += 1;
+                continue;
+            }
+            ("--list", Some(value)) => {
+                list = value.parse().unwrap_or_else(|_| usage(&args[0]));
+            }
+            ("--repl", _) => {
+                interactive = true;
+                i += 1;
+                continue;
+            }
+            ("--repair", _) => {
+                repair = true;
+                i += 1;
+                continue;
+            }
+            ("--input", Some(value)) => filename = Some(value.clone()),
+            ("--explain", Some(value)) => explain = Some(value.clone()),
+            ("--dot", Some(value)) => dot = Some(value.clone()),
+            _ => usage(&args[0]),
+        }
+        i += 2;
     }
 
-    if args[1] != "--input" {
-        eprintln!("Usage: {} --input <filename>", args[0]);
+    let Some(filename) = filename else {
+        usage(&args[0]);
+    };
+
+    let contents = fs::read_to_string(&filename).expect("Something went wrong reading the file");
+
+    let input = parse::parse(&contents).unwrap_or_else(|e| {
+        eprintln!("Invalid input in {}: {}", filename, e);
         std::process::exit(1);
-    }
+    });
 
-    let filename = &args[2];
-    let contents = fs::read_to_string(filename).expect("Something went wrong reading the file");
+    for warning in &input.warnings {
+        eprintln!("Warning: {}", warning);
+    }
 
-    let mut page_map: HashMap<u16, Vec<u16>> = HashMap::new();
-    let map_re = Regex::new(MAP_REGEX).unwrap();
+    let rules = input.rules;
+    let updates = input.updates;
 
-    let mut updates: Vec<Vec<u16>> = Vec::new();
-    let update_re = Regex::new(UPDATE_REGEX).unwrap();
+    if interactive {
+        let mut queue = print_queue(rules, updates);
+        repl::run(&mut queue, io::stdin().lock(), io::stdout()).expect("Could not run the REPL");
+        return;
+    }
 
-    for line in contents.lines() {
-        if map_re.is_match(line) {
-            let caps = map_re.captures(line).unwrap();
-            let key = caps[1].parse::<u16>().unwrap();
-            let value = caps[2].parse::<u16>().unwrap();
+    if let Some(format) = explain {
+        let report = explain::explain(&rules, &updates);
 
-            if page_map.contains_key(&key) {
-                let pages = page_map.get_mut(&key).unwrap();
-                pages.push(value);
-            } else {
-                let mut pages: Vec<u16> = Vec::new();
-                pages.push(value);
-                page_map.insert(key, pages);
-            }
-        } else if update_re.is_match(line) {
-            let mut update: Vec<u16> = Vec::new();
-            for cap in update_re.captures_iter(line) {
-                update.push(cap[1].parse::<u16>().unwrap());
-            }
-            updates.push(update);
+        match format.as_str() {
+            "text" => explain::print_text(&report),
+            "json" => explain::print_json(&report),
+            _ => usage(&args[0]),
         }
+        return;
     }
 
-    let mut part_1 = 0;
-    let mut part_2 = 0;
+    if let Some(which) = dot {
+        if which == "all" {
+            print!("{}", dot::rule_graph(&rules, &updates));
+            return;
+        }
 
-    for update in updates {
-        if !check_valid(&page_map, &update) {
-            let mut sorted = update.clone();
+        // Updates are numbered from 1
+        let number = match which.parse::<usize>() {
+            Ok(number) if (1..=updates.len()).contains(&number) => number,
+            _ => {
+                eprintln!(
+                    "--dot takes all or an update number from 1 to {}",
+                    updates.len()
+                );
+                std::process::exit(1);
+            }
+        };
 
-            sorted.sort_by(|a, b| {
-                if !page_map.contains_key(a) {
-                    return Ordering::Greater;
-                }
+        print!(
+            "{}",
+            dot::update_graph(&rules, &updates[number - 1], number)
+        );
+        return;
+    }
 
-                let page = page_map.get(a).unwrap();
+    if orderings {
+        print_orderings(&rules, &updates, list);
+        return;
+    }
 
-                if !page.contains(b) {
-                    return Ordering::Greater;
-                }
+    if repair {
+        print_repairs(&rules, &updates);
+        return;
+    }
+
+    let queue = print_queue(rules, updates);
 
-                return Ordering::Less;
-            });
+    // Part 2 can't be trusted if an invalid update has no valid order
+    let mut cycles = queue.cycles().peekable();
+    if cycles.peek().is_some() {
+        for (id, cycle) in cycles {
+            eprintln!("Update {}: {}", id, cycle);
+        }
+        std::process::exit(1);
+    }
 
-            part_2 += sorted[(sorted.len() as f64 / 2.0).floor() as usize] as u32;
-        } else {
-            part_1 += update[(update.len() as f64 / 2.0).floor() as usize] as u32;
+    for (id, status) in queue.updates() {
+        if let (true, Ok(middle)) = (status.ambiguous, &status.middle) {
+            eprintln!(
+                "Update {}: more than one order satisfies the rules, using middle page {}",
+                id, middle
+            );
         }
     }
 
-    println!("Part 1: {}", part_1);
-    println!("Part 2: {}", part_2);
+    println!("Part 1: {}", queue.part_1());
+    println!("Part 2: {}", queue.part_2());
 }
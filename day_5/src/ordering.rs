@@ -0,0 +1,119 @@
+use std::collections::HashSet;
+use std::fmt;
+
+#[derive(Debug)]
+pub struct Ordered {
+    pub pages: Vec<u16>,
+    // Where each of pages was in the update
+    pub positions: Vec<usize>,
+    // More than one order satisfies the rules; pages is the one that keeps
+    // unconstrained pages closest to where they were
+    pub ambiguous: bool,
+}
+
+// Rules that contradict each other within an update
+#[derive(Debug)]
+pub struct Cycle {
+    pub pages: Vec<u16>,
+}
+
+impl fmt::Display for Cycle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let pages: Vec<String> = self.pages.iter().map(|p| p.to_string()).collect();
+        write!(
+            f,
+            "rules form a cycle: {} -> {}",
+            pages.join(" -> "),
+            self.pages[0]
+        )
+    }
+}
+
+pub fn must_precede(rules: &HashSet<(u16, u16)>, before: u16, after: u16) -> bool {
+    rules.contains(&(before, after))
+}
+
+// Follows edges backwards from a position that Kahn's algorithm couldn't
+// place until one repeats, which closes a cycle
+fn find_cycle(predecessors: &[Vec<usize>], placed: &[bool], update: &[u16]) -> Cycle {
+    let start = (0..update.len()).find(|&i| !placed[i]).unwrap();
+    let mut path: Vec<usize> = vec![start];
+
+    loop {
+        let current = path[path.len() - 1];
+        // Anything left unplaced still has an unplaced predecessor
+        let previous = *predecessors[current].iter().find(|&&p| !placed[p]).unwrap();
+
+        if let Some(seen) = path.iter().position(|&i| i == previous) {
+            let mut cycle: Vec<u16> = path[seen..].iter().map(|&i| update[i]).collect();
+            // The path was walked backwards
+            cycle.reverse();
+            return Cycle { pages: cycle };
+        }
+
+        path.push(previous);
+    }
+}
+
+// For each position in the update, the positions whose pages the rules say
+// have to come after it
+pub fn rule_edges(rules: &HashSet<(u16, u16)>, update: &[u16]) -> Vec<Vec<usize>> {
+    (0..update.len())
+        .map(|i| {
+            (0..update.len())
+                .filter(|&j| must_precede(rules, update[i], update[j]))
+                .collect()
+        })
+        .collect()
+}
+
+// Kahn's algorithm over edges between positions in the update. When several
+// pages are free to go next the one that came first in the update wins.
+pub fn topological_order(update: &[u16], successors: &[Vec<usize>]) -> Result<Ordered, Cycle> {
+    let n = update.len();
+    let mut predecessors: Vec<Vec<usize>> = vec![Vec::new(); n];
+
+    for (i, afters) in successors.iter().enumerate() {
+        for &j in afters {
+            predecessors[j].push(i);
+        }
+    }
+
+    let mut in_degree: Vec<usize> = predecessors.iter().map(|p| p.len()).collect();
+    let mut placed = vec![false; n];
+    let mut ready: Vec<usize> = (0..n).filter(|&i| in_degree[i] == 0).collect();
+    let mut positions: Vec<usize> = Vec::with_capacity(n);
+    let mut ambiguous = false;
+
+    while !ready.is_empty() {
+        if ready.len() > 1 {
+            ambiguous = true;
+        }
+
+        let next = ready.remove(0);
+        placed[next] = true;
+        positions.push(next);
+
+        for &j in &successors[next] {
+            in_degree[j] -= 1;
+            if in_degree[j] == 0 {
+                let at = ready.partition_point(|&r| r < j);
+                ready.insert(at, j);
+            }
+        }
+    }
+
+    if positions.len() < n {
+        return Err(find_cycle(&predecessors, &placed, update));
+    }
+
+    Ok(Ordered {
+        pages: positions.iter().map(|&i| update[i]).collect(),
+        positions,
+        ambiguous,
+    })
+}
+
+pub fn order_update(rules: &HashSet<(u16, u16)>, update: &[u16]) -> Result<Ordered, Cycle> {
+    topological_order(update, &rule_edges(rules, update))
+}
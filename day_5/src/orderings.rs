@@ -0,0 +1,121 @@
+use crate::ordering::rule_edges;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+// Placed sets are bitmasks of positions
+pub const MAX_PAGES: usize = 64;
+// Rules that leave many pages unconstrained reach up to 2^n placed sets
+pub const MAX_STATES: usize = 1 << 22;
+
+#[derive(Debug)]
+pub enum Limit {
+    TooManyPages,
+    TooManyStates,
+    Overflow,
+}
+
+impl fmt::Display for Limit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Limit::TooManyPages => write!(f, "more than {} pages", MAX_PAGES),
+            Limit::TooManyStates => write!(
+                f,
+                "more than {} partial orderings to count through",
+                MAX_STATES
+            ),
+            Limit::Overflow => write!(f, "too many orderings to count"),
+        }
+    }
+}
+
+// For each position, the positions the rules say have to come before it
+fn required(rules: &HashSet<(u16, u16)>, update: &[u16]) -> Vec<u64> {
+    let mut required = vec![0u64; update.len()];
+
+    for (i, afters) in rule_edges(rules, update).iter().enumerate() {
+        for &j in afters {
+            required[j] |= 1 << i;
+        }
+    }
+
+    required
+}
+
+// The number of orders of the update that break no rules. Each placed set
+// counts the ways its pages can fill the front of the update; only sets
+// closed under the rules are ever reached, so a well constrained update
+// stays cheap however long it is.
+pub fn count_orderings(rules: &HashSet<(u16, u16)>, update: &[u16]) -> Result<u128, Limit> {
+    let n = update.len();
+    if n > MAX_PAGES {
+        return Err(Limit::TooManyPages);
+    }
+
+    let required = required(rules, update);
+    let mut layer: HashMap<u64, u128> = HashMap::from([(0, 1)]);
+    let mut states = 1;
+
+    for _ in 0..n {
+        let mut next: HashMap<u64, u128> = HashMap::new();
+
+        for (&placed, &count) in &layer {
+            for i in (0..n).filter(|&i| placed & (1 << i) == 0 && required[i] & !placed == 0) {
+                let ways = next.entry(placed | (1 << i)).or_default();
+                *ways = ways.checked_add(count).ok_or(Limit::Overflow)?;
+            }
+        }
+
+        states += next.len();
+        if states > MAX_STATES {
+            return Err(Limit::TooManyStates);
+        }
+
+        layer = next;
+    }
+
+    Ok(layer.values().sum())
+}
+
+fn extend(
+    update: &[u16],
+    required: &[u64],
+    placed: u64,
+    order: &mut Vec<u16>,
+    max: usize,
+    orderings: &mut Vec<Vec<u16>>,
+) {
+    if orderings.len() == max {
+        return;
+    }
+
+    if order.len() == update.len() {
+        orderings.push(order.clone());
+        return;
+    }
+
+    for i in 0..update.len() {
+        if placed & (1 << i) == 0 && required[i] & !placed == 0 {
+            order.push(update[i]);
+            extend(update, required, placed | (1 << i), order, max, orderings);
+            order.pop();
+        }
+    }
+}
+
+// Up to max of the valid orders, favouring pages that came earlier in the
+// update at each step
+pub fn list_orderings(
+    rules: &HashSet<(u16, u16)>,
+    update: &[u16],
+    max: usize,
+) -> Result<Vec<Vec<u16>>, Limit> {
+    if update.len() > MAX_PAGES {
+        return Err(Limit::TooManyPages);
+    }
+
+    let required = required(rules, update);
+    let mut orderings: Vec<Vec<u16>> = Vec::new();
+    extend(update, &required, 0, &mut Vec::new(), max, &mut orderings);
+
+    Ok(orderings)
+}
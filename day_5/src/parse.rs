@@ -0,0 +1,193 @@
+use std::collections::{HashMap, HashSet};
+
+#[derive(Debug, Default)]
+pub struct Input {
+    pub rules: HashSet<(u16, u16)>,
+    pub updates: Vec<Vec<u16>>,
+    // Problems that don't stop the input being used
+    pub warnings: Vec<String>,
+}
+
+fn page(text: &str, line: usize) -> Result<u16, String> {
+    let text = text.trim();
+    text.parse::<u16>()
+        .map_err(|_| format!("Line {}: '{}' is not a page number", line, text))
+}
+
+pub fn parse_rule(text: &str, line: usize) -> Result<(u16, u16), String> {
+    let Some((before, after)) = text.split_once('|') else {
+        let hint = if text.contains(',') {
+            " (rules and updates need a blank line between them)"
+        } else {
+            ""
+        };
+        return Err(format!(
+            "Line {}: expected a rule like 47|53, found '{}'{}",
+            line, text, hint
+        ));
+    };
+
+    let (before, after) = (page(before, line)?, page(after, line)?);
+    check_rule(before, after).map_err(|e| format!("Line {}: {}", line, e))?;
+
+    Ok((before, after))
+}
+
+// A rule putting a page before itself can never be satisfied
+pub fn check_rule(before: u16, after: u16) -> Result<(), String> {
+    if before == after {
+        return Err(format!(
+            "rule {}|{} puts a page before itself",
+            before, after
+        ));
+    }
+
+    Ok(())
+}
+
+// Pages that appear more than once in an update, each listed once
+pub fn repeated_pages(update: &[u16]) -> Vec<u16> {
+    let mut seen: HashSet<u16> = HashSet::new();
+    let mut repeated: Vec<u16> = Vec::new();
+
+    for &page in update {
+        if !seen.insert(page) && !repeated.contains(&page) {
+            repeated.push(page);
+        }
+    }
+
+    repeated
+}
+
+pub fn parse_update(text: &str, line: usize) -> Result<Vec<u16>, String> {
+    if text.contains('|') {
+        return Err(format!(
+            "Line {}: expected an update like 75,47,61, found the rule '{}'",
+            line, text
+        ));
+    }
+
+    text.split(',').map(|p| page(p, line)).collect()
+}
+
+// The rules, one per line, then a blank line, then the updates, one per
+// line. Blank lines are allowed after the updates but nowhere else.
+pub fn parse(contents: &str) -> Result<Input, String> {
+    let mut input = Input::default();
+    // Where each rule was first seen, to point duplicates back at it
+    let mut rule_lines: HashMap<(u16, u16), usize> = HashMap::new();
+    let mut in_updates = false;
+    let mut blank: Option<usize> = None;
+
+    for (i, text) in contents.lines().enumerate() {
+        let line = i + 1;
+        let text = text.trim();
+
+        if text.is_empty() {
+            if !in_updates {
+                if input.rules.is_empty() {
+                    return Err(format!(
+                        "Line {}: expected a rule before any blank line",
+                        line
+                    ));
+                }
+                in_updates = true;
+            } else {
+                blank.get_or_insert(line);
+            }
+            continue;
+        }
+
+        if !in_updates {
+            let rule = parse_rule(text, line)?;
+
+            if let Some(first) = rule_lines.get(&rule) {
+                input.warnings.push(format!(
+                    "Line {}: rule {}|{} repeats line {}",
+                    line, rule.0, rule.1, first
+                ));
+            } else {
+                rule_lines.insert(rule, line);
+                input.rules.insert(rule);
+            }
+            continue;
+        }
+
+        if let Some(blank) = blank {
+            if input.updates.is_empty() {
+                return Err(format!(
+                    "Line {}: more than one blank line between the rules and the updates",
+                    blank
+                ));
+            }
+
+            return Err(format!(
+                "Line {}: blank line inside the updates section",
+                blank
+            ));
+        }
+
+        let update = parse_update(text, line)?;
+
+        for page in repeated_pages(&update) {
+            input.warnings.push(format!(
+                "Line {}: page {} appears more than once in the update",
+                line, page
+            ));
+        }
+
+        input.updates.push(update);
+    }
+
+    if input.rules.is_empty() {
+        return Err("No rules found".to_string());
+    }
+
+    if !in_updates {
+        return Err("Expected a blank line after the rules, then the updates".to_string());
+    }
+
+    if input.updates.is_empty() {
+        return Err("No updates found after the rules".to_string());
+    }
+
+    Ok(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_self_rule() {
+        assert_eq!(
+            parse_rule("5|5", 3),
+            Err("Line 3: rule 5|5 puts a page before itself".to_string())
+        );
+        assert_eq!(
+            parse("47|53\n5|5\n\n47,5,53\n").err().unwrap(),
+            "Line 2: rule 5|5 puts a page before itself"
+        );
+    }
+
+    #[test]
+    fn locates_extra_blank_lines() {
+        assert_eq!(
+            parse("47|53\n\n\n47,53\n").err().unwrap(),
+            "Line 3: more than one blank line between the rules and the updates"
+        );
+        assert_eq!(
+            parse("47|53\n\n47,53\n\n53,47\n").err().unwrap(),
+            "Line 4: blank line inside the updates section"
+        );
+    }
+
+    #[test]
+    fn parses_rules_and_updates() {
+        let input = parse("47|53\n97|47\n47|53\n\n97,47,53\n\n").unwrap();
+
+        assert_eq!(input.rules, HashSet::from([(47, 53), (97, 47)]));
+        assert_eq!(input.updates, vec![vec![97, 47, 53]]);
+        assert_eq!(input.warnings, vec!["Line 3: rule 47|53 repeats line 1"]);
+    }
+}
@@ -0,0 +1,259 @@
+use crate::explain::find_violations;
+use crate::ordering::{order_update, Cycle};
+use crate::parse::check_rule;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+#[derive(Debug)]
+pub struct Status {
+    pub pages: Vec<u16>,
+    pub valid: bool,
+    // The middle page once ordered; an error for an invalid update whose
+    // rules contradict each other
+    pub middle: Result<u16, Cycle>,
+    // More than one order satisfies the rules, so the middle page is only one
+    // of the possibilities
+    pub ambiguous: bool,
+}
+
+fn middle(pages: &[u16]) -> u16 {
+    pages[pages.len() / 2]
+}
+
+fn status(rules: &HashSet<(u16, u16)>, pages: Vec<u16>) -> Status {
+    let valid = find_violations(rules, &pages).is_empty();
+    let (middle, ambiguous) = if valid {
+        (Ok(middle(&pages)), false)
+    } else {
+        match order_update(rules, &pages) {
+            Ok(ordered) => (Ok(middle(&ordered.pages)), ordered.ambiguous),
+            Err(cycle) => (Err(cycle), false),
+        }
+    };
+
+    Status {
+        pages,
+        valid,
+        middle,
+        ambiguous,
+    }
+}
+
+// Rules and updates that can change over time. A change only re-checks the
+// updates it can affect and adjusts the part 1 and part 2 sums as it goes.
+#[derive(Debug, Default)]
+pub struct PrintQueue {
+    rules: HashSet<(u16, u16)>,
+    // Numbered from 1 in the order they were added; numbers aren't reused
+    updates: BTreeMap<usize, Status>,
+    next_id: usize,
+    // The updates each page appears in
+    containing: HashMap<u16, BTreeSet<usize>>,
+    part_1: u32,
+    part_2: u32,
+}
+
+impl PrintQueue {
+    pub fn new(rules: HashSet<(u16, u16)>, updates: Vec<Vec<u16>>) -> Result<PrintQueue, String> {
+        for &(before, after) in &rules {
+            check_rule(before, after)?;
+        }
+
+        let mut queue = PrintQueue {
+            rules,
+            next_id: 1,
+            ..PrintQueue::default()
+        };
+
+        for pages in updates {
+            queue.add_update(pages)?;
+        }
+
+        Ok(queue)
+    }
+
+    pub fn rules(&self) -> &HashSet<(u16, u16)> {
+        &self.rules
+    }
+
+    pub fn updates(&self) -> impl Iterator<Item = (usize, &Status)> {
+        self.updates.iter().map(|(&id, status)| (id, status))
+    }
+
+    pub fn part_1(&self) -> u32 {
+        self.part_1
+    }
+
+    // Invalid updates can't be reordered when their rules contradict each
+    // other, so they are left out of part 2
+    pub fn part_2(&self) -> u32 {
+        self.part_2
+    }
+
+    // The updates left out of part 2, with the cycle in their rules
+    pub fn cycles(&self) -> impl Iterator<Item = (usize, &Cycle)> {
+        self.updates()
+            .filter_map(|(id, status)| Some((id, status.middle.as_ref().err()?)))
+    }
+
+    fn count(&mut self, status: &Status) {
+        match (status.valid, &status.middle) {
+            (true, Ok(page)) => self.part_1 += *page as u32,
+            (false, Ok(page)) => self.part_2 += *page as u32,
+            _ => {}
+        }
+    }
+
+    fn uncount(&mut self, status: &Status) {
+        match (status.valid, &status.middle) {
+            (true, Ok(page)) => self.part_1 -= *page as u32,
+            (false, Ok(page)) => self.part_2 -= *page as u32,
+            _ => {}
+        }
+    }
+
+    // An update needs a middle page, so it can't be empty
+    pub fn add_update(&mut self, pages: Vec<u16>) -> Result<usize, String> {
+        if pages.is_empty() {
+            return Err("An update needs at least one page".to_string());
+        }
+
+        let id = self.next_id;
+        self.next_id += 1;
+
+        for &page in &pages {
+            self.containing.entry(page).or_default().insert(id);
+        }
+
+        let status = status(&self.rules, pages);
+        self.count(&status);
+        self.updates.insert(id, status);
+
+        Ok(id)
+    }
+
+    pub fn remove_update(&mut self, id: usize) -> Option<Vec<u16>> {
+        let status = self.updates.remove(&id)?;
+        self.uncount(&status);
+
+        for page in &status.pages {
+            if let Some(ids) = self.containing.get_mut(page) {
+                ids.remove(&id);
+                if ids.is_empty() {
+                    self.containing.remove(page);
+                }
+            }
+        }
+
+        Some(status.pages)
+    }
+
+    // A rule only matters to updates with both of its pages
+    fn recheck(&mut self, before: u16, after: u16) {
+        let (Some(with_before), Some(with_after)) =
+            (self.containing.get(&before), self.containing.get(&after))
+        else {
+            return;
+        };
+        let affected: Vec<usize> = with_before.intersection(with_after).copied().collect();
+
+        for id in affected {
+            let old = self.updates.remove(&id).unwrap();
+            self.uncount(&old);
+
+            let new = status(&self.rules, old.pages);
+            self.count(&new);
+            self.updates.insert(id, new);
+        }
+    }
+
+    // False if the rule was already there
+    pub fn add_rule(&mut self, before: u16, after: u16) -> Result<bool, String> {
+        check_rule(before, after)?;
+
+        if !self.rules.insert((before, after)) {
+            return Ok(false);
+        }
+
+        self.recheck(before, after);
+        Ok(true)
+    }
+
+    // False if there was no such rule
+    pub fn remove_rule(&mut self, before: u16, after: u16) -> bool {
+        if !self.rules.remove(&(before, after)) {
+            return false;
+        }
+
+        self.recheck(before, after);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(pairs: &[(u16, u16)]) -> HashSet<(u16, u16)> {
+        pairs.iter().copied().collect()
+    }
+
+    #[test]
+    fn sums_follow_rule_changes() {
+        let mut queue = PrintQueue::new(
+            rules(&[(1, 2), (2, 3)]),
+            vec![vec![1, 2, 3], vec![3, 2, 1], vec![4, 6]],
+        )
+        .unwrap();
+        assert_eq!((queue.part_1(), queue.part_2()), (2 + 6, 2));
+
+        assert_eq!(queue.add_rule(6, 4), Ok(true));
+        assert_eq!((queue.part_1(), queue.part_2()), (2, 2 + 4));
+
+        assert!(queue.remove_rule(6, 4));
+        assert!(!queue.remove_rule(6, 4));
+        assert_eq!((queue.part_1(), queue.part_2()), (2 + 6, 2));
+    }
+
+    #[test]
+    fn rejects_empty_updates() {
+        let mut queue = PrintQueue::new(rules(&[(1, 2)]), vec![vec![1, 2]]).unwrap();
+
+        assert_eq!(
+            queue.add_update(Vec::new()),
+            Err("An update needs at least one page".to_string())
+        );
+        assert_eq!(queue.updates().count(), 1);
+        assert_eq!((queue.part_1(), queue.part_2()), (2, 0));
+
+        assert!(PrintQueue::new(rules(&[(1, 2)]), vec![vec![1, 2], Vec::new()]).is_err());
+    }
+
+    #[test]
+    fn rejects_self_rules() {
+        let mut queue = PrintQueue::new(rules(&[(1, 2)]), vec![vec![5, 1, 2]]).unwrap();
+
+        assert_eq!(
+            queue.add_rule(5, 5),
+            Err("rule 5|5 puts a page before itself".to_string())
+        );
+        assert!(!queue.rules().contains(&(5, 5)));
+        assert_eq!(queue.part_1(), 1);
+
+        assert!(PrintQueue::new(rules(&[(5, 5)]), vec![vec![5]]).is_err());
+    }
+
+    #[test]
+    fn cycles_are_left_out_of_part_2() {
+        let mut queue = PrintQueue::new(rules(&[(1, 2), (2, 3)]), vec![vec![3, 1, 2]]).unwrap();
+        assert_eq!(queue.part_2(), 2);
+        assert_eq!(queue.cycles().count(), 0);
+
+        queue.add_rule(3, 1).unwrap();
+        assert_eq!(queue.part_2(), 0);
+        assert_eq!(queue.cycles().map(|(id, _)| id).collect::<Vec<_>>(), [1]);
+
+        queue.remove_rule(3, 1);
+        assert_eq!(queue.part_2(), 2);
+        assert_eq!(queue.cycles().count(), 0);
+    }
+}
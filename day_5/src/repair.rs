@@ -0,0 +1,276 @@
+use crate::ordering::{rule_edges, topological_order, Cycle};
+use std::collections::{HashMap, HashSet};
+
+// Searching every order for the fewest swaps takes time and memory
+// exponential in the length of the update
+pub const MAX_SWAP_PAGES: usize = 20;
+
+// Take page out from position from and put it back in so that it ends up at
+// position to
+#[derive(Debug)]
+pub struct Move {
+    pub page: u16,
+    pub from: usize,
+    pub to: usize,
+}
+
+// Exchange the pages at position and position + 1
+#[derive(Debug)]
+pub struct Swap {
+    pub position: usize,
+    pub left: u16,
+    pub right: u16,
+}
+
+#[derive(Debug)]
+pub struct Repair {
+    pub moves: Vec<Move>,
+    pub moved: Vec<u16>,
+    // None when the update is longer than MAX_SWAP_PAGES
+    pub swaps: Option<Vec<Swap>>,
+    pub swapped: Option<Vec<u16>>,
+}
+
+// reach[i][j] when the rules force position i's page somewhere before
+// position j's, directly or through other pages in the update
+fn reachability(successors: &[Vec<usize>]) -> Vec<Vec<bool>> {
+    let n = successors.len();
+    let mut reach = vec![vec![false; n]; n];
+
+    for (start, row) in reach.iter_mut().enumerate() {
+        let mut stack: Vec<usize> = successors[start].clone();
+
+        while let Some(i) = stack.pop() {
+            if !row[i] {
+                row[i] = true;
+                stack.extend(&successors[i]);
+            }
+        }
+    }
+
+    reach
+}
+
+fn augment(
+    i: usize,
+    edges: &[Vec<usize>],
+    visited: &mut [bool],
+    matched_left: &mut [Option<usize>],
+    matched_right: &mut [Option<usize>],
+) -> bool {
+    for &j in &edges[i] {
+        if visited[j] {
+            continue;
+        }
+        visited[j] = true;
+
+        if matched_right[j].is_none_or(|k| augment(k, edges, visited, matched_left, matched_right))
+        {
+            matched_left[i] = Some(j);
+            matched_right[j] = Some(i);
+            return true;
+        }
+    }
+
+    false
+}
+
+// The largest set of positions that can all stay where they are. Two pages
+// can't both stay if the later one has to come before the earlier one, and
+// that relation is a partial order, so this is a maximum antichain: found
+// from a maximum matching with König's theorem.
+fn keepers(reach: &[Vec<bool>]) -> Vec<bool> {
+    let n = reach.len();
+    let edges: Vec<Vec<usize>> = (0..n)
+        .map(|i| (i + 1..n).filter(|&j| reach[j][i]).collect())
+        .collect();
+
+    let mut matched_left: Vec<Option<usize>> = vec![None; n];
+    let mut matched_right: Vec<Option<usize>> = vec![None; n];
+
+    for i in 0..n {
+        let mut visited = vec![false; n];
+        augment(
+            i,
+            &edges,
+            &mut visited,
+            &mut matched_left,
+            &mut matched_right,
+        );
+    }
+
+    // Everything reachable by alternating paths from unmatched left vertices
+    let mut left_seen = vec![false; n];
+    let mut right_seen = vec![false; n];
+    let mut stack: Vec<usize> = (0..n).filter(|&i| matched_left[i].is_none()).collect();
+
+    while let Some(i) = stack.pop() {
+        if left_seen[i] {
+            continue;
+        }
+        left_seen[i] = true;
+
+        for &j in &edges[i] {
+            if !right_seen[j] {
+                right_seen[j] = true;
+                if let Some(k) = matched_right[j] {
+                    stack.push(k);
+                }
+            }
+        }
+    }
+
+    // Neither copy is in the minimum vertex cover
+    (0..n).map(|i| left_seen[i] && !right_seen[i]).collect()
+}
+
+// Moves each position not in keep to where it belongs in target, in target
+// order, so that every page already in place stays in target order
+fn plan_moves(update: &[u16], keep: &[bool], target: &[usize]) -> Vec<Move> {
+    let mut rank = vec![0; update.len()];
+    for (r, &i) in target.iter().enumerate() {
+        rank[i] = r;
+    }
+
+    let mut placed = keep.to_vec();
+    let mut current: Vec<usize> = (0..update.len()).collect();
+    let mut moves: Vec<Move> = Vec::new();
+
+    for &i in target.iter().filter(|&&i| !keep[i]) {
+        let from = current.iter().position(|&c| c == i).unwrap();
+        current.remove(from);
+
+        let to = current
+            .iter()
+            .rposition(|&c| placed[c] && rank[c] < rank[i])
+            .map_or(0, |p| p + 1);
+        current.insert(to, i);
+        placed[i] = true;
+
+        moves.push(Move {
+            page: update[i],
+            from,
+            to,
+        });
+    }
+
+    moves
+}
+
+// The order needing the fewest adjacent swaps, which is the number of pairs
+// it flips. Placing pages front to back, a page costs one swap for each page
+// still to be placed that was before it in the update. States are the sets
+// already placed; only those closed under the rules are ever reached.
+fn fewest_swaps(successors: &[Vec<usize>]) -> Vec<usize> {
+    let n = successors.len();
+    let mut required = vec![0u32; n];
+    for (i, afters) in successors.iter().enumerate() {
+        for &j in afters {
+            required[j] |= 1 << i;
+        }
+    }
+
+    // For each placed set, its cost and the set and position it came from
+    let mut best: HashMap<u32, (u32, u32, usize)> = HashMap::new();
+    best.insert(0, (0, 0, 0));
+    let mut layer: Vec<u32> = vec![0];
+
+    for _ in 0..n {
+        let mut next: Vec<u32> = Vec::new();
+
+        for &placed in &layer {
+            let cost = best[&placed].0;
+
+            for i in (0..n).filter(|&i| placed & (1 << i) == 0 && required[i] & !placed == 0) {
+                let before_unplaced = ((1u32 << i) - 1) & !placed;
+                let state = placed | (1 << i);
+                let total = cost + before_unplaced.count_ones();
+
+                match best.get(&state) {
+                    Some(&(existing, _, _)) if existing <= total => {}
+                    Some(_) => {
+                        best.insert(state, (total, placed, i));
+                    }
+                    None => {
+                        best.insert(state, (total, placed, i));
+                        next.push(state);
+                    }
+                }
+            }
+        }
+
+        layer = next;
+    }
+
+    let mut order: Vec<usize> = Vec::with_capacity(n);
+    let mut state = ((1u64 << n) - 1) as u32;
+    while state != 0 {
+        let (_, previous, i) = best[&state];
+        order.push(i);
+        state = previous;
+    }
+
+    order.reverse();
+    order
+}
+
+// Bubble sorts the update into target, which swaps each flipped pair once
+fn plan_swaps(update: &[u16], target: &[usize]) -> Vec<Swap> {
+    let mut rank = vec![0; update.len()];
+    for (r, &i) in target.iter().enumerate() {
+        rank[i] = r;
+    }
+
+    let mut current: Vec<usize> = (0..update.len()).collect();
+    let mut swaps: Vec<Swap> = Vec::new();
+
+    for end in (1..current.len()).rev() {
+        for position in 0..end {
+            if rank[current[position]] > rank[current[position + 1]] {
+                current.swap(position, position + 1);
+                swaps.push(Swap {
+                    position,
+                    left: update[current[position + 1]],
+                    right: update[current[position]],
+                });
+            }
+        }
+    }
+
+    swaps
+}
+
+pub fn repair(rules: &HashSet<(u16, u16)>, update: &[u16]) -> Result<Repair, Cycle> {
+    let mut successors = rule_edges(rules, update);
+
+    // Contradictory rules can't be satisfied by any number of moves
+    topological_order(update, &successors)?;
+
+    let (swaps, swapped) = if update.len() <= MAX_SWAP_PAGES {
+        let target = fewest_swaps(&successors);
+        let swapped = target.iter().map(|&i| update[i]).collect();
+        (Some(plan_swaps(update, &target)), Some(swapped))
+    } else {
+        (None, None)
+    };
+
+    let keep = keepers(&reachability(&successors));
+
+    // Pages that stay keep their relative order; the rest go wherever the
+    // rules allow around them
+    let kept: Vec<usize> = (0..update.len()).filter(|&i| keep[i]).collect();
+    for pair in kept.windows(2) {
+        successors[pair[0]].push(pair[1]);
+    }
+
+    let target = topological_order(update, &successors)
+        .expect("Keeping a consistent set in place can't create a cycle")
+        .positions;
+
+    Ok(Repair {
+        moves: plan_moves(update, &keep, &target),
+        moved: target.iter().map(|&i| update[i]).collect(),
+        swaps,
+        swapped,
+    })
+}
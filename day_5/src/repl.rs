@@ -0,0 +1,131 @@
+use crate::parse::{parse_rule, parse_update, repeated_pages};
+use crate::queue::PrintQueue;
+use std::io::{self, BufRead, Write};
+
+const HELP: &str = "\
+add rule <X|Y>         add a rule
+remove rule <X|Y>      remove a rule
+add update <A,B,...>   add an update
+remove update <n>      remove update n
+list                   show every update and whether it is valid
+sums                   show the part 1 and part 2 sums
+help                   show this message
+quit                   stop";
+
+fn join(pages: &[u16]) -> String {
+    let pages: Vec<String> = pages.iter().map(|p| p.to_string()).collect();
+    pages.join(",")
+}
+
+fn list(queue: &PrintQueue, writer: &mut impl Write) -> io::Result<()> {
+    for (id, status) in queue.updates() {
+        let state = match (status.valid, &status.middle) {
+            (true, _) => "valid".to_string(),
+            (false, Ok(_)) => "invalid".to_string(),
+            (false, Err(cycle)) => format!("invalid, {}", cycle),
+        };
+
+        writeln!(writer, "{:>4}  {}  ({})", id, join(&status.pages), state)?;
+    }
+
+    Ok(())
+}
+
+fn sums(queue: &PrintQueue, writer: &mut impl Write) -> io::Result<()> {
+    writeln!(writer, "Part 1: {}", queue.part_1())?;
+    writeln!(writer, "Part 2: {}", queue.part_2())?;
+
+    let left_out: Vec<String> = queue.cycles().map(|(id, _)| id.to_string()).collect();
+    if !left_out.is_empty() {
+        writeln!(
+            writer,
+            "Left out of part 2, their rules form a cycle: {}",
+            left_out.join(", ")
+        )?;
+    }
+
+    Ok(())
+}
+
+// Runs one command, returning the message to show when it couldn't be. Line
+// is used in the locations of parse errors.
+fn execute(
+    queue: &mut PrintQueue,
+    command: &str,
+    line: usize,
+    writer: &mut impl Write,
+) -> io::Result<Result<(), String>> {
+    let words: Vec<&str> = command.split_whitespace().collect();
+
+    match words.as_slice() {
+        ["add", "rule", rule] => match parse_rule(rule, line) {
+            Ok((before, after)) => match queue.add_rule(before, after) {
+                Ok(true) => sums(queue, writer)?,
+                Ok(false) => return Ok(Err(format!("{}|{} is already a rule", before, after))),
+                Err(e) => return Ok(Err(e)),
+            },
+            Err(e) => return Ok(Err(e)),
+        },
+        ["remove", "rule", rule] => match parse_rule(rule, line) {
+            Ok((before, after)) if queue.remove_rule(before, after) => sums(queue, writer)?,
+            Ok((before, after)) => return Ok(Err(format!("{}|{} isn't a rule", before, after))),
+            Err(e) => return Ok(Err(e)),
+        },
+        ["add", "update", pages] => match parse_update(pages, line) {
+            Ok(pages) => match queue.add_update(pages.clone()) {
+                Ok(id) => {
+                    for page in repeated_pages(&pages) {
+                        writeln!(
+                            writer,
+                            "Warning: Line {}: page {} appears more than once in the update",
+                            line, page
+                        )?;
+                    }
+                    writeln!(writer, "Added update {}", id)?;
+                    sums(queue, writer)?;
+                }
+                Err(e) => return Ok(Err(e)),
+            },
+            Err(e) => return Ok(Err(e)),
+        },
+        ["remove", "update", id] => match id
+            .parse::<usize>()
+            .ok()
+            .and_then(|id| queue.remove_update(id))
+        {
+            Some(_) => sums(queue, writer)?,
+            None => return Ok(Err(format!("There is no update {}", id))),
+        },
+        ["list"] => list(queue, writer)?,
+        ["sums"] => sums(queue, writer)?,
+        ["help"] => writeln!(writer, "{}", HELP)?,
+        [] => {}
+        _ => return Ok(Err(format!("Unknown command '{}', try help", command))),
+    }
+
+    Ok(Ok(()))
+}
+
+// Reads commands until quit or the end of input
+pub fn run(queue: &mut PrintQueue, reader: impl BufRead, mut writer: impl Write) -> io::Result<()> {
+    write!(writer, "> ")?;
+    writer.flush()?;
+
+    for (i, command) in reader.lines().enumerate() {
+        let command = command?;
+        let command = command.trim();
+
+        if command == "quit" {
+            break;
+        }
+
+        if let Err(e) = execute(queue, command, i + 1, &mut writer)? {
+            writeln!(writer, "{}", e)?;
+        }
+
+        write!(writer, "> ")?;
+        writer.flush()?;
+    }
+
+    Ok(())
+}
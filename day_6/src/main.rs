@@ -101,12 +101,12 @@ impl ops::Sub<Direction> for Point {
     }
 }
 
-fn grid_contains(grid: &Vec<Vec<GridPoint>>, point: Point) -> bool {
+fn grid_contains(grid: &[Vec<GridPoint>], point: Point) -> bool {
     point.x < grid[0].len() as i32 && point.y < grid.len() as i32 && point.x >= 0 && point.y >= 0
 }
 
 // If it returns to the same point and direction, it's an infinite loop
-fn has_cycle(grid: &Vec<Vec<GridPoint>>, point: Point, direction: Direction) -> bool {
+fn has_cycle(grid: &[Vec<GridPoint>], point: Point, direction: Direction) -> bool {
     let mut seen: HashSet<(Point, Direction)> = HashSet::new();
     seen.insert((point, direction));
 
@@ -133,6 +133,88 @@ fn has_cycle(grid: &Vec<Vec<GridPoint>>, point: Point, direction: Direction) ->
     false
 }
 
+fn guard_direction(c: char) -> Option<Direction> {
+    match c {
+        '^' => Some(Direction::Up),
+        '>' => Some(Direction::Right),
+        'v' => Some(Direction::Down),
+        '<' => Some(Direction::Left),
+        _ => None,
+    }
+}
+
+// The grid along with where the guard starts and which way they face. There
+// has to be exactly one guard, and the map must be rectangular. Blank lines
+// are only allowed at the end.
+fn parse_map(contents: &str) -> Result<(Vec<Vec<GridPoint>>, Point, Direction), String> {
+    let mut grid: Vec<Vec<GridPoint>> = Vec::new();
+    let mut guard: Option<(Point, Direction)> = None;
+
+    let mut lines: Vec<&str> = contents.lines().collect();
+    while lines.last().is_some_and(|line| line.is_empty()) {
+        lines.pop();
+    }
+
+    for (y, line) in lines.into_iter().enumerate() {
+        if line.is_empty() {
+            return Err(format!("Line {} is blank", y + 1));
+        }
+
+        let mut row: Vec<GridPoint> = Vec::new();
+
+        for (x, c) in line.chars().enumerate() {
+            let point = Point {
+                x: x as i32,
+                y: y as i32,
+            };
+
+            if let Some(direction) = guard_direction(c) {
+                if let Some((first, _)) = guard {
+                    return Err(format!(
+                        "More than one guard, at ({}, {}) and ({}, {})",
+                        first.x, first.y, point.x, point.y
+                    ));
+                }
+
+                guard = Some((point, direction));
+            } else if c != '#' && c != '.' {
+                return Err(format!(
+                    "Invalid character '{}' at ({}, {})",
+                    c, point.x, point.y
+                ));
+            }
+
+            row.push(GridPoint {
+                traversed: guard.is_some_and(|(start, _)| start == point),
+                obstacle: c == '#',
+                infinite_loop: false,
+            });
+        }
+
+        if let Some(first) = grid.first() {
+            if row.len() != first.len() {
+                return Err(format!(
+                    "Line {} has {} cells but line 1 has {}, the map must be rectangular",
+                    y + 1,
+                    row.len(),
+                    first.len()
+                ));
+            }
+        }
+
+        grid.push(row);
+    }
+
+    if grid.is_empty() {
+        return Err("The map is empty".to_string());
+    }
+
+    match guard {
+        Some((start, direction)) => Ok((grid, start, direction)),
+        None => Err("No guard (^, >, v or <) in the map".to_string()),
+    }
+}
+
 fn main() {
     let args: Vec<String> = env::args().collect();
 
@@ -150,53 +232,10 @@ fn main() {
     let filename = &args[2];
     let contents = fs::read_to_string(filename).expect("Something went wrong reading the file");
 
-    let mut grid: Vec<Vec<GridPoint>> = Vec::new();
-    let mut start = Point { x: 0, y: 0 };
-    let mut direction = Direction::Up;
-
-    let mut x = 0;
-    let mut y = 0;
-
-    for line in contents.lines() {
-        let mut row: Vec<GridPoint> = Vec::new();
-        for c in line.chars() {
-            match c {
-                '^' => {
-                    row.push(GridPoint {
-                        traversed: true,
-                        obstacle: false,
-                        infinite_loop: false,
-                    });
-                    start = Point { x, y };
-                    direction = Direction::Up;
-                }
-                '#' => {
-                    row.push(GridPoint {
-                        traversed: false,
-                        obstacle: true,
-                        infinite_loop: false,
-                    });
-                }
-                '.' => {
-                    row.push(GridPoint {
-                        traversed: false,
-                        obstacle: false,
-                        infinite_loop: false,
-                    });
-                }
-                _ => {
-                    eprintln!("Invalid character in input file: {}", c);
-                    std::process::exit(1);
-                }
-            }
-
-            x += 1;
-        }
-
-        grid.push(row);
-        y += 1;
-        x = 0;
-    }
+    let (mut grid, start, mut direction) = parse_map(&contents).unwrap_or_else(|e| {
+        eprintln!("Invalid map in {}: {}", filename, e);
+        std::process::exit(1);
+    });
 
     let mut current = start;
 
@@ -236,3 +275,52 @@ fn main() {
     println!("Part 1: {}", part_1);
     println!("Part 2: {}", part_2);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_guard_facing_any_way() {
+        for (glyph, expected) in [
+            ('^', Direction::Up),
+            ('>', Direction::Right),
+            ('v', Direction::Down),
+            ('<', Direction::Left),
+        ] {
+            let (grid, start, direction) = parse_map(&format!("..#\n.{}.\n", glyph)).unwrap();
+
+            assert_eq!((grid.len(), grid[0].len()), (2, 3));
+            assert_eq!(start, Point { x: 1, y: 1 });
+            assert_eq!(direction, expected);
+        }
+    }
+
+    #[test]
+    fn needs_exactly_one_guard() {
+        assert_eq!(
+            parse_map(".^.\n.>.\n").err().unwrap(),
+            "More than one guard, at (1, 0) and (1, 1)"
+        );
+        assert_eq!(
+            parse_map("...\n...\n").err().unwrap(),
+            "No guard (^, >, v or <) in the map"
+        );
+    }
+
+    #[test]
+    fn rejects_ragged_rows() {
+        assert_eq!(
+            parse_map("...\n.>\n").err().unwrap(),
+            "Line 2 has 2 cells but line 1 has 3, the map must be rectangular"
+        );
+    }
+
+    #[test]
+    fn blank_lines_only_allowed_at_end() {
+        assert!(parse_map("...\n.^.\n\n\n").is_ok());
+        assert_eq!(parse_map("\n.^.\n").err().unwrap(), "Line 1 is blank");
+        assert_eq!(parse_map(".^.\n\n...\n").err().unwrap(), "Line 2 is blank");
+        assert_eq!(parse_map("\n\n").err().unwrap(), "The map is empty");
+    }
+}